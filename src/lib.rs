@@ -1,15 +1,25 @@
-use std::{collections::BTreeMap, iter::FusedIterator};
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, BTreeSet, BinaryHeap, TryReserveError, VecDeque},
+    fmt,
+    io::{self, BufRead},
+    iter::FusedIterator,
+    ops::{Add, RangeBounds},
+};
 
 /// A node in the graph is identified by the key.
 /// Keys are stored in the order they were inserted, a redundant copy is stored in the index.
 /// Values don't have this redundancy.
 /// There could be more than one values for a key.
+/// Edges can carry a weight of type `E`, unweighted graphs use `()`.
+/// A key can have any number of outgoing edges, parallel edges included.
 #[derive(Debug, Clone)]
-pub struct IndexedGraph<K, V> {
+pub struct IndexedGraph<K, V, E = ()> {
     keys: Vec<K>,
     values: Vec<V>,
-    edges: BTreeMap<K, K>,
+    edges: BTreeMap<K, Vec<(K, E)>>,
     i: BTreeMap<K, Vec<usize>>,
+    max_nodes: Option<usize>,
     // phantom: PhantomData<&'a V>,
 }
 
@@ -27,23 +37,283 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// use igraph::IndexedGraph;
     /// let mut graph = IndexedGraph::new();
     ///
-    /// assert_eq!(core::mem::size_of::<IndexedGraph<u8,u8>>(), 96);
-    /// assert_eq!(core::mem::size_of_val(&graph), 96);
+    /// assert_eq!(core::mem::size_of::<IndexedGraph<u8,u8>>(), 112);
+    /// assert_eq!(core::mem::size_of_val(&graph), 112);
     ///
     /// // entries can now be inserted into the empty graph
     /// graph.insert(1, "a");
     /// ```
     pub fn new() -> IndexedGraph<K, V> {
+        IndexedGraph::new_weighted()
+    }
+
+    /// Builds a graph from an adjacency map, with an edge from every key to each of its listed
+    /// neighbors. Every key and every neighbor becomes a node once, with `V::default()` as value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use igraph::IndexedGraph;
+    ///
+    /// let adjacency = BTreeMap::from([(1, vec![2, 3]), (2, vec![3])]);
+    /// let graph: IndexedGraph<i32, ()> = IndexedGraph::from_adjacency(adjacency);
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 3);
+    /// assert_eq!(graph.edge_list(), vec![(1, 2), (1, 3), (2, 3)]);
+    /// ```
+    pub fn from_adjacency(adjacency: BTreeMap<K, Vec<K>>) -> IndexedGraph<K, V>
+    where
+        V: Default,
+    {
+        let mut graph = IndexedGraph::new();
+        for (from, targets) in &adjacency {
+            for key in std::iter::once(from).chain(targets) {
+                if !graph.contains_key(key) {
+                    graph.insert(key.clone(), V::default());
+                }
+            }
+        }
+        for (from, targets) in adjacency {
+            for to in targets {
+                graph.insert_edge(from.clone(), to);
+            }
+        }
+        graph
+    }
+
+    /// Collapses every chain of keys with exactly one incoming and one outgoing edge into a single
+    /// edge, from the key before the chain to the key after it. The keys inside the chains are
+    /// removed, and their entries are returned in insertion order.
+    /// Cycles made only of such keys have no start and are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, key * 10);
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4), (1, 5)]);
+    /// assert_eq!(graph.compress_chains(), [(2, 20), (3, 30)]);
+    /// assert_eq!(graph.edge_list(), vec![(1, 4), (1, 5)]);
+    /// assert_eq!(graph.node_count(), 3);
+    /// ```
+    pub fn compress_chains(&mut self) -> Vec<(K, V)> {
+        let mut in_degrees: BTreeMap<&K, usize> = BTreeMap::new();
+        for (_, to, _) in self.edge_triples() {
+            *in_degrees.entry(to).or_default() += 1;
+        }
+        let inner = |key: &K| -> bool {
+            self.i.contains_key(key)
+                && in_degrees.get(key) == Some(&1)
+                && self
+                    .edges
+                    .get(key)
+                    .is_some_and(|targets| targets.len() == 1 && &targets[0].0 != key)
+        };
+        let mut chained = BTreeSet::new();
+        let mut edges: BTreeMap<K, Vec<(K, ())>> = BTreeMap::new();
+        for (from, targets) in &self.edges {
+            if inner(from) {
+                continue;
+            }
+            let collapsed = targets
+                .iter()
+                .map(|(to, _)| {
+                    let mut end = to;
+                    while inner(end) {
+                        chained.insert(end.clone());
+                        end = &self.edges[end][0].0;
+                    }
+                    (end.clone(), ())
+                })
+                .collect();
+            edges.insert(from.clone(), collapsed);
+        }
+        for (from, targets) in &self.edges {
+            if inner(from) && !chained.contains(from) {
+                edges.insert(from.clone(), targets.clone());
+            }
+        }
+        self.edges = edges;
+        self.remove_entries(|_, key| chained.contains(key))
+    }
+}
+
+impl IndexedGraph<String, ()> {
+    /// Builds a graph from a text edge list, with one whitespace separated `from to` pair per line.
+    /// Both ends of every edge are inserted as nodes, once.
+    /// Blank lines and lines starting with `#` are skipped.
+    ///
+    /// Returns an `InvalidData` error for lines which don't hold exactly two fields.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let input = b"# a comment\na b\n\nb c\n";
+    /// let graph = IndexedGraph::from_edge_list_reader(&input[..]).unwrap();
+    /// assert_eq!(graph.len(), 3);
+    /// assert_eq!(
+    ///     graph.shortest_path(&"a".to_string(), &"c".to_string()),
+    ///     Some(vec!["a".to_string(), "b".to_string(), "c".to_string()])
+    /// );
+    /// assert!(IndexedGraph::from_edge_list_reader(&b"a b c\n"[..]).is_err());
+    /// ```
+    pub fn from_edge_list_reader<R: BufRead>(reader: R) -> io::Result<IndexedGraph<String, ()>> {
+        let mut graph = IndexedGraph::new();
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 2 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected `from to`, got {:?}", number + 1, line),
+                ));
+            }
+            for key in &fields {
+                if !graph.contains_key(&key.to_string()) {
+                    graph.insert(key.to_string(), ());
+                }
+            }
+            graph.insert_edge(fields[0].to_string(), fields[1].to_string());
+        }
+        Ok(graph)
+    }
+
+    /// Builds a graph from a `source,target` CSV edge list, as written by `write_csv`.
+    /// A leading `source,target` header is skipped, and both ends of every edge are inserted
+    /// as nodes, once. Fields may be quoted to hold commas, quotes doubled, or line breaks.
+    ///
+    /// Returns an `InvalidData` error for records which don't hold exactly two fields,
+    /// or when the input ends inside a quoted field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert("a".to_string(), ());
+    /// graph.insert("b,c".to_string(), ());
+    /// graph.insert_edge("a".to_string(), "b,c".to_string());
+    /// graph.insert_edge("b,c".to_string(), "a".to_string());
+    /// let mut buffer = Vec::new();
+    /// graph.write_csv(&mut buffer).unwrap();
+    ///
+    /// let read = IndexedGraph::from_csv(&buffer[..]).unwrap();
+    /// assert_eq!(read.edge_list(), graph.edge_list());
+    /// assert!(IndexedGraph::from_csv(&b"a,b,c\n"[..]).is_err());
+    /// ```
+    pub fn from_csv<R: BufRead>(reader: R) -> io::Result<IndexedGraph<String, ()>> {
+        // splits a record into its fields, or returns `None` while a quoted field is open
+        fn fields(record: &str) -> Option<Vec<String>> {
+            let mut fields = vec![];
+            let mut field = String::new();
+            let mut quoted = false;
+            let mut chars = record.chars().peekable();
+            while let Some(c) = chars.next() {
+                if quoted {
+                    if c != '"' {
+                        field.push(c);
+                    } else if chars.peek() == Some(&'"') {
+                        field.push('"');
+                        chars.next();
+                    } else {
+                        quoted = false;
+                    }
+                } else if c == ',' {
+                    fields.push(std::mem::take(&mut field));
+                } else if c == '"' && field.is_empty() {
+                    quoted = true;
+                } else {
+                    field.push(c);
+                }
+            }
+            if quoted {
+                return None;
+            }
+            fields.push(field);
+            Some(fields)
+        }
+
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut graph = IndexedGraph::new();
+        let mut record = String::new();
+        let mut first = true;
+        for (number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if !record.is_empty() {
+                record.push('\n');
+            }
+            record.push_str(line.trim_end_matches('\r'));
+            let fields = match fields(&record) {
+                Some(fields) => fields,
+                None => continue,
+            };
+            let record = std::mem::take(&mut record);
+            if record.is_empty() {
+                continue;
+            }
+            if std::mem::take(&mut first) && fields == ["source", "target"] {
+                continue;
+            }
+            if fields.len() != 2 {
+                return Err(invalid(format!(
+                    "line {}: expected `source,target`, got {:?}",
+                    number + 1,
+                    record
+                )));
+            }
+            for key in &fields {
+                if !graph.contains_key(key) {
+                    graph.insert(key.clone(), ());
+                }
+            }
+            let mut fields = fields.into_iter();
+            graph.insert_edge(fields.next().unwrap(), fields.next().unwrap());
+        }
+        if !record.is_empty() {
+            return Err(invalid("unterminated quoted field".to_string()));
+        }
+        Ok(graph)
+    }
+}
+
+impl<K: Ord + Clone, V, E> IndexedGraph<K, V, E> {
+    /// Makes a new, empty `IndexedGraph` with edge weights of type `E`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_weighted_edge(1, 2, 0.5);
+    /// ```
+    pub fn new_weighted() -> IndexedGraph<K, V, E> {
         IndexedGraph {
             keys: vec![],
             values: vec![],
             edges: BTreeMap::new(),
             i: BTreeMap::new(),
+            max_nodes: None,
             // phantom: PhantomData,
         }
     }
 
     /// Clears the graph, removing all elements.
+    /// The limit set by `set_max_nodes` is kept.
     ///
     /// # Examples
     ///
@@ -54,10 +324,125 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// graph.insert(1, "a");
     /// graph.clear();
     /// // assert!(graph.is_empty());
+    ///
+    /// graph.set_max_nodes(1);
+    /// graph.clear();
+    /// assert_eq!(graph.try_insert_capped(1, "a"), Ok(()));
+    /// assert_eq!(graph.try_insert_capped(2, "b"), Err("b"));
     /// ```
     pub fn clear(&mut self) {
         // Let's just drop everything.
-        *self = IndexedGraph::new();
+        let max_nodes = self.max_nodes;
+        *self = IndexedGraph::new_weighted();
+        self.max_nodes = max_nodes;
+    }
+
+    /// Clears the graph, removing all elements but keeping the allocated capacity of the vectors.
+    /// Useful when a graph of similar size is rebuilt right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.clear_keep_capacity();
+    /// assert!(graph.is_empty());
+    /// // no reallocation needed to insert again
+    /// assert_eq!(graph.insert_checked(3, "c"), (0, false));
+    /// ```
+    pub fn clear_keep_capacity(&mut self) {
+        self.keys.clear();
+        self.values.clear();
+        self.edges.clear();
+        self.i.clear();
+    }
+
+    /// Tries to reserve capacity for at least `additional` more entries.
+    /// Returns an error instead of aborting when the allocation fails.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph: IndexedGraph<u32, &str> = IndexedGraph::new();
+    /// graph.try_reserve(10).expect("cannot reserve");
+    /// assert_eq!(graph.insert_checked(1, "a"), (0, false));
+    /// assert!(graph.try_reserve(usize::MAX).is_err());
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        self.keys.try_reserve(additional)?;
+        self.values.try_reserve(additional)
+    }
+
+    /// Records how many entries the graph holds and how many edges start from each key,
+    /// so that `rollback_to` can later drop whatever was appended after this point.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    /// let checkpoint = graph.checkpoint();
+    ///
+    /// graph.insert(3, "c");
+    /// graph.insert(1, "d");
+    /// graph.insert_edge(1, 3);
+    /// graph.insert_edge(3, 2);
+    /// assert_eq!(graph.rollback_to(checkpoint.clone()), Ok(()));
+    /// assert_eq!(graph.len(), 2);
+    /// assert_eq!(graph.get(&1), vec![&"a"]);
+    /// assert!(!graph.contains_key(&3));
+    /// assert_eq!(graph.edge_list(), vec![(1, 2)]);
+    ///
+    /// graph.pop_last();
+    /// assert!(graph.rollback_to(checkpoint).is_err());
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint<K> {
+        Checkpoint {
+            entries: self.keys.len(),
+            edges: self
+                .edges
+                .iter()
+                .map(|(from, targets)| (from.clone(), targets.len()))
+                .collect(),
+        }
+    }
+
+    /// Truncates the graph back to the state recorded by `checkpoint`, dropping the entries
+    /// and edges inserted since. This assumes the graph was only appended to in between.
+    ///
+    /// Returns a `RollbackError` and leaves the graph unchanged if it holds fewer entries,
+    /// or a key fewer edges, than at the checkpoint. Other changes, like a removal followed
+    /// by as many insertions, can't be detected.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint<K>) -> Result<(), RollbackError> {
+        let Checkpoint { entries, edges } = checkpoint;
+        let shrunk = edges.iter().any(|(from, count)| {
+            self.edges
+                .get(from)
+                .map_or(true, |targets| targets.len() < *count)
+        });
+        if self.keys.len() < entries || shrunk {
+            return Err(RollbackError);
+        }
+        self.keys.truncate(entries);
+        self.values.truncate(entries);
+        self.edges.retain(|from, targets| match edges.get(from) {
+            Some(count) => {
+                targets.truncate(*count);
+                true
+            }
+            None => false,
+        });
+        self.rebuild_index();
+        Ok(())
     }
 
     /// Returns a reference to the values corresponding to the key.
@@ -73,13 +458,86 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// assert_eq!(graph.get(&2), Vec::<&&str>::new());
     /// ```
     pub fn get(&self, key: &K) -> Vec<&V> {
-        let mut res = vec![];
-        if let Some(indexes) = self.i.get(key) {
-            for idx in indexes {
-                res.push(&self.values[*idx]);
-            }
-        }
-        return res;
+        self.values_for(key).collect()
+    }
+
+    /// Gets an iterator over the values corresponding to the key, without allocating.
+    /// The values are yielded in the order they were inserted for that key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "c");
+    /// assert_eq!(graph.values_for(&1).collect::<Vec<_>>(), [&"a", &"c"]);
+    /// assert_eq!(graph.values_for(&3).next(), None);
+    /// ```
+    pub fn values_for(&self, key: &K) -> impl Iterator<Item = &V> + '_ {
+        self.indexes_of(key)
+            .iter()
+            .map(move |idx| &self.values[*idx])
+    }
+
+    /// Returns the values corresponding to the key, or `None` if the key is absent.
+    /// Unlike `get`, this tells an absent key apart from a present key without values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(1, "b");
+    /// assert_eq!(graph.get_checked(&1), Some(vec![&"a", &"b"]));
+    /// assert_eq!(graph.get_checked(&2), None);
+    /// ```
+    pub fn get_checked(&self, key: &K) -> Option<Vec<&V>> {
+        let indexes = self.i.get(key)?;
+        Some(indexes.iter().map(|idx| &self.values[*idx]).collect())
+    }
+
+    /// Returns the key as stored in the graph, which is the first inserted instance of the key.
+    /// Useful when keys compare equal without being identical.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use igraph::IndexedGraph;
+    ///
+    /// // a key which ignores case when compared
+    /// #[derive(Debug, Clone, Eq)]
+    /// struct Name(String);
+    /// impl PartialEq for Name {
+    ///     fn eq(&self, other: &Self) -> bool {
+    ///         self.cmp(other) == Ordering::Equal
+    ///     }
+    /// }
+    /// impl PartialOrd for Name {
+    ///     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    ///         Some(self.cmp(other))
+    ///     }
+    /// }
+    /// impl Ord for Name {
+    ///     fn cmp(&self, other: &Self) -> Ordering {
+    ///         self.0.to_lowercase().cmp(&other.0.to_lowercase())
+    ///     }
+    /// }
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(Name("Alice".to_string()), 1);
+    /// graph.insert(Name("ALICE".to_string()), 2);
+    /// let stored = graph.get_key(&Name("alice".to_string())).unwrap();
+    /// assert_eq!(stored.0, "Alice");
+    /// assert_eq!(graph.get_key(&Name("bob".to_string())), None);
+    /// ```
+    pub fn get_key(&self, key: &K) -> Option<&K> {
+        self.i.get_key_value(key).map(|(key, _)| key)
     }
 
     /// Returns the key-value pairs corresponding to the supplied key.
@@ -101,7 +559,7 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
                 res.push((tup.0, &self.values[*idx]));
             }
         }
-        return res;
+        res
     }
 
     /// Returns the first key-value pair in the graph.
@@ -146,13 +604,37 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// }
     /// assert!(graph.is_empty());
     /// ```
+    ///
+    /// Only the popped value of a duplicate key is removed, the later ones stay reachable.
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "c");
+    /// assert_eq!(graph.pop_first(), Some((1, "a")));
+    /// assert_eq!(graph.get(&1), vec![&"c"]);
+    /// assert_eq!(graph.get(&2), vec![&"b"]);
+    /// ```
     pub fn pop_first(&mut self) -> Option<(K, V)> {
         if self.keys.is_empty() {
             None
         } else {
             let key = self.keys.remove(0);
             let value = self.values.remove(0);
-            self.i.remove(&key);
+            let indexes = self.i.get_mut(&key).unwrap();
+            indexes.remove(0);
+            if indexes.is_empty() {
+                self.i.remove(&key);
+            }
+            // every remaining entry moved one position to the front
+            for indexes in self.i.values_mut() {
+                for idx in indexes.iter_mut() {
+                    *idx -= 1;
+                }
+            }
             Some((key, value))
         }
     }
@@ -172,7 +654,7 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// ```
     pub fn last_key_value(&self) -> Option<(&K, &V)> {
         if let Some(key) = self.keys.last() {
-            Some((key, &self.values.last().unwrap()))
+            Some((key, self.values.last().unwrap()))
         } else {
             None
         }
@@ -196,13 +678,30 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// }
     /// assert!(graph.is_empty());
     /// ```
+    ///
+    /// Only the popped value of a duplicate key is removed, the earlier ones stay reachable.
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(1, "b");
+    /// assert_eq!(graph.pop_last(), Some((1, "b")));
+    /// assert!(graph.contains_key(&1));
+    /// assert_eq!(graph.get(&1), vec![&"a"]);
+    /// ```
     pub fn pop_last(&mut self) -> Option<(K, V)> {
         if self.keys.is_empty() {
             None
         } else {
             let key = self.keys.pop().unwrap();
             let value = self.values.pop().unwrap();
-            self.i.remove(&key);
+            let indexes = self.i.get_mut(&key).unwrap();
+            indexes.pop();
+            if indexes.is_empty() {
+                self.i.remove(&key);
+            }
             Some((key, value))
         }
     }
@@ -220,7 +719,62 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// assert_eq!(graph.contains_key(&2), false);
     /// ```
     pub fn contains_key(&self, key: &K) -> bool {
-        self.i.get(key).is_some()
+        self.i.contains_key(key)
+    }
+
+    /// Returns `true` if any key of the graph falls in the range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(5, "b");
+    /// assert!(graph.contains_key_range(2..=5));
+    /// assert!(!graph.contains_key_range(2..5));
+    /// ```
+    pub fn contains_key_range<R: RangeBounds<K>>(&self, range: R) -> bool {
+        self.i.range(range).next().is_some()
+    }
+
+    /// Returns `true` if the graph contains at least one of the keys.
+    /// Stops at the first key found, an empty slice gives `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// assert!(graph.contains_any(&[3, 2]));
+    /// assert!(!graph.contains_any(&[3, 4]));
+    /// assert!(!graph.contains_any(&[]));
+    /// ```
+    pub fn contains_any(&self, keys: &[K]) -> bool {
+        keys.iter().any(|key| self.i.contains_key(key))
+    }
+
+    /// Returns `true` if the graph contains every one of the keys.
+    /// Stops at the first missing key, an empty slice gives `true`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// assert!(graph.contains_all(&[2, 1]));
+    /// assert!(!graph.contains_all(&[1, 3]));
+    /// assert!(graph.contains_all(&[]));
+    /// ```
+    pub fn contains_all(&self, keys: &[K]) -> bool {
+        keys.iter().all(|key| self.i.contains_key(key))
     }
 
     /// Inserts a key-value pair into the graph.
@@ -252,90 +806,4105 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
         }
         self.values.push(value);
         self.keys.push(key);
-        return self.values.last();
+        self.values.last()
     }
 
-    /// Inserts a key-value pair into the graph.
-    ///
-    /// If the graph did not have this key present, `None` is returned.
+    /// Inserts a key-value pair, choosing with `policy` what happens if the key is already
+    /// present. A missing key is always inserted like `insert`.
     ///
-    /// If the graph did have this key present, the value is inserted after the existing one.
-    /// Then the new value is returned.
-    /// The key is not updated, only inserted the first time.
+    /// Returns the value back as `Err` when it is rejected.
     ///
     /// # Examples
     ///
     /// ```
-    /// use igraph::IndexedGraph;
+    /// use igraph::{DuplicatePolicy, IndexedGraph};
     ///
     /// let mut graph = IndexedGraph::new();
-    /// assert_eq!(graph.insert(37, "a"), Some(&"a"));
-    /// assert_eq!(graph.insert(12, "b"), Some(&"b"));
-    /// assert_eq!(graph.is_empty(), false);
-    ///
-    /// graph.insert_edge(12, 37);
-    /// assert_eq!(graph.insert(37, "c"), Some(&"c"));
-    /// //assert_eq!(graph[&37], "c");
+    /// graph.insert(1, "a");
+    /// assert_eq!(graph.insert_with_policy(1, "b", DuplicatePolicy::Append), Ok(()));
+    /// assert_eq!(graph.get(&1), vec![&"a", &"b"]);
+    /// assert_eq!(graph.insert_with_policy(1, "c", DuplicatePolicy::Replace), Ok(()));
+    /// assert_eq!(graph.get(&1), vec![&"a", &"c"]);
+    /// assert_eq!(graph.insert_with_policy(1, "d", DuplicatePolicy::Reject), Err("d"));
+    /// assert_eq!(graph.get(&1), vec![&"a", &"c"]);
+    /// assert_eq!(graph.insert_with_policy(2, "e", DuplicatePolicy::Reject), Ok(()));
     /// ```
-    pub fn insert_edge(&mut self, from: K, to: K) -> Option<(&K, &K)> {
-        self.edges.insert(from.clone(), to);
-        self.edges.get_key_value(&from)
+    pub fn insert_with_policy(
+        &mut self,
+        key: K,
+        value: V,
+        policy: DuplicatePolicy,
+    ) -> Result<(), V> {
+        let latest = match self.i.get(&key).and_then(|indexes| indexes.last()) {
+            Some(idx) => *idx,
+            None => {
+                self.insert(key, value);
+                return Ok(());
+            }
+        };
+        match policy {
+            DuplicatePolicy::Append => {
+                self.insert(key, value);
+            }
+            DuplicatePolicy::Replace => self.values[latest] = value,
+            DuplicatePolicy::Reject => return Err(value),
+        }
+        Ok(())
     }
 
-    /// Returns the number of elements in the graph.
+    /// Limits how many distinct keys `try_insert_capped` lets the graph grow to.
+    /// Keys already in the graph are kept even if there are more of them than `cap`.
+    ///
+    /// Only `try_insert_capped` honors the limit. `insert`, `insert_with_policy`, `insert_lru`,
+    /// `merge_with` and every other method adding keys ignore it, and can grow the graph past it.
     ///
     /// # Examples
     ///
     /// ```
     /// use igraph::IndexedGraph;
     ///
-    /// let mut a = IndexedGraph::new();
-    /// assert_eq!(a.len(), 0);
-    /// a.insert(1, "a");
-    /// assert_eq!(a.len(), 1);
+    /// let mut graph = IndexedGraph::new();
+    /// graph.set_max_nodes(2);
+    /// assert_eq!(graph.try_insert_capped(1, "a"), Ok(()));
+    /// assert_eq!(graph.try_insert_capped(2, "b"), Ok(()));
+    /// assert_eq!(graph.try_insert_capped(3, "c"), Err("c"));
+    /// assert_eq!(graph.try_insert_capped(1, "d"), Ok(()));
+    /// assert_eq!(graph.node_count(), 2);
     /// ```
-    pub fn len(&self) -> usize {
-        self.i.len()
+    pub fn set_max_nodes(&mut self, cap: usize) {
+        self.max_nodes = Some(cap);
     }
 
-    /// Returns `true` if the graph contains no elements.
-    ///
-    /// # Examples
+    /// Inserts a key-value pair like `insert`, unless the key is new and the graph already
+    /// holds as many distinct keys as allowed by `set_max_nodes`.
+    /// Returns the value back as `Err` when it is rejected.
+    pub fn try_insert_capped(&mut self, key: K, value: V) -> Result<(), V> {
+        let full = self.max_nodes.is_some_and(|cap| self.i.len() >= cap);
+        if full && !self.i.contains_key(&key) {
+            return Err(value);
+        }
+        self.insert(key, value);
+        Ok(())
+    }
+
+    /// Inserts a key-value pair like `insert`, then evicts the oldest keys while there are more
+    /// than `max` distinct keys. The oldest key is the one of the first entry in insertion order,
+    /// all of its values are removed, along with the edges starting from or pointing to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert_lru(1, "a", 2);
+    /// graph.insert_lru(2, "b", 2);
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_lru(2, "c", 2);
+    /// assert_eq!(graph.node_count(), 2);
+    /// graph.insert_lru(3, "d", 2);
+    /// assert!(!graph.contains_key(&1));
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&2, &"b"), (&2, &"c"), (&3, &"d")]);
+    /// assert_eq!(graph.edge_count(), 0);
+    ///
+    /// // shrinking the limit evicts several keys at once
+    /// graph.insert_lru(4, "e", 1);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&4, &"e")]);
+    /// ```
+    pub fn insert_lru(&mut self, key: K, value: V, max: usize) {
+        self.insert(key, value);
+        let excess = self.i.len().saturating_sub(max);
+        if excess == 0 {
+            return;
+        }
+        let mut oldest = BTreeSet::new();
+        for key in &self.keys {
+            if oldest.len() == excess {
+                break;
+            }
+            oldest.insert(key);
+        }
+        let oldest: Vec<K> = oldest.into_iter().cloned().collect();
+        self.remove_all(&oldest);
+    }
+
+    /// Inserts a key-value pair into the graph, like `insert`.
+    ///
+    /// Returns the position the value was stored at,
+    /// and whether the `keys` or `values` vector had to reallocate to make room for it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// // the empty graph has no capacity yet
+    /// assert_eq!(graph.insert_checked(37, "a"), (0, true));
+    /// assert_eq!(graph.insert_checked(12, "b"), (1, false));
+    /// ```
+    pub fn insert_checked(&mut self, key: K, value: V) -> (usize, bool) {
+        let keys_capacity = self.keys.capacity();
+        let values_capacity = self.values.capacity();
+        let idx = self.values.len();
+        self.insert(key, value);
+        let reallocated =
+            self.keys.capacity() != keys_capacity || self.values.capacity() != values_capacity;
+        (idx, reallocated)
+    }
+
+    /// Inserts an edge into the graph, with the default weight.
+    ///
+    /// A key can have many outgoing edges: the new edge is added after the existing ones,
+    /// even if there already is an edge between the same keys. Earlier versions kept a single
+    /// outgoing edge per key and replaced it, use `set_edge_weight` to update an existing edge
+    /// or `dedup_edges` to drop parallel ones.
+    ///
+    /// Returns the ends of the inserted edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// assert_eq!(graph.insert(37, "a"), Some(&"a"));
+    /// assert_eq!(graph.insert(12, "b"), Some(&"b"));
+    /// assert_eq!(graph.is_empty(), false);
+    ///
+    /// assert_eq!(graph.insert_edge(12, 37), Some((&12, &37)));
+    /// graph.insert_edge(12, 12);
+    /// graph.insert_edge(12, 37);
+    /// assert_eq!(graph.edge_list(), vec![(12, 37), (12, 12), (12, 37)]);
+    /// assert_eq!(graph.insert(37, "c"), Some(&"c"));
+    /// //assert_eq!(graph[&37], "c");
+    /// ```
+    pub fn insert_edge(&mut self, from: K, to: K) -> Option<(&K, &K)>
+    where
+        E: Default,
+    {
+        self.insert_weighted_edge(from, to, E::default())
+    }
+
+    /// Inserts an edge with a weight into the graph.
+    /// A key can have many outgoing edges, new edges are added after the existing ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// assert_eq!(graph.insert_weighted_edge(1, 2, 7), Some((&1, &2)));
+    /// ```
+    pub fn insert_weighted_edge(&mut self, from: K, to: K, weight: E) -> Option<(&K, &K)> {
+        if let Some(targets) = self.edges.get_mut(&from) {
+            targets.push((to, weight));
+        } else {
+            self.edges.insert(from.clone(), vec![(to, weight)]);
+        }
+        self.edges
+            .get_key_value(&from)
+            .and_then(|(from, targets)| targets.last().map(|(to, _)| (from, to)))
+    }
+
+    /// Updates the weight of an existing edge, returning the previous weight.
+    /// If there is no such edge, `None` is returned and no edge is created.
+    /// With parallel edges only the first one is updated.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_weighted_edge(1, 2, 10);
+    /// assert_eq!(graph.set_edge_weight(&1, &2, 15), Some(10));
+    /// assert_eq!(graph.set_edge_weight(&1, &2, 20), Some(15));
+    /// assert_eq!(graph.set_edge_weight(&2, &1, 5), None);
+    /// ```
+    pub fn set_edge_weight(&mut self, from: &K, to: &K, weight: E) -> Option<E> {
+        let targets = self.edges.get_mut(from)?;
+        let (_, old) = targets.iter_mut().find(|(target, _)| target == to)?;
+        Some(std::mem::replace(old, weight))
+    }
+
+    /// Inserts every edge of the iterator, like `insert_edge`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (1, 3)]);
+    /// assert_eq!(graph.edge_count(), 3);
+    /// ```
+    pub fn extend_edges<I: IntoIterator<Item = (K, K)>>(&mut self, edges: I)
+    where
+        E: Default,
+    {
+        for (from, to) in edges {
+            self.insert_edge(from, to);
+        }
+    }
+
+    /// Reserves capacity for at least `additional` more outgoing edges of the node.
+    /// Worth it before inserting a burst of edges from the same key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(0, ());
+    /// graph.reserve_edges(&0, 10);
+    /// let capacity = graph.edge_capacity(&0);
+    /// assert!(capacity >= 10);
+    /// for to in 1..=10 {
+    ///     graph.insert_edge(0, to);
+    /// }
+    /// assert_eq!(graph.edge_capacity(&0), capacity);
+    /// ```
+    pub fn reserve_edges(&mut self, node: &K, additional: usize) {
+        if let Some(targets) = self.edges.get_mut(node) {
+            targets.reserve(additional);
+        } else {
+            self.edges
+                .insert(node.clone(), Vec::with_capacity(additional));
+        }
+    }
+
+    /// Returns the number of outgoing edges the node can hold without reallocating.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, ());
+    /// assert_eq!(graph.edge_capacity(&1), 0);
+    /// graph.insert_edge(1, 1);
+    /// assert!(graph.edge_capacity(&1) >= 1);
+    /// ```
+    pub fn edge_capacity(&self, node: &K) -> usize {
+        self.edges.get(node).map_or(0, |targets| targets.capacity())
+    }
+
+    /// Inserts an edge like `insert_edge`, but only if the graph stays acyclic.
+    /// The edge is rejected when `from` is already reachable from `to`, including self loops,
+    /// so a graph built only with this method is always a DAG.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::{CycleError, IndexedGraph};
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// assert_eq!(graph.insert_edge_acyclic(1, 2), Ok(()));
+    /// assert_eq!(graph.insert_edge_acyclic(2, 3), Ok(()));
+    /// assert_eq!(graph.insert_edge_acyclic(1, 3), Ok(()));
+    /// assert_eq!(graph.insert_edge_acyclic(3, 1), Err(CycleError));
+    /// assert_eq!(graph.insert_edge_acyclic(2, 2), Err(CycleError));
+    /// assert_eq!(graph.edge_count(), 3);
+    /// ```
+    pub fn insert_edge_acyclic(&mut self, from: K, to: K) -> Result<(), CycleError>
+    where
+        E: Default,
+    {
+        if from == to || self.descendants(&to).contains(&from) {
+            return Err(CycleError);
+        }
+        self.insert_edge(from, to);
+        Ok(())
+    }
+
+    /// Inserts an edge like `insert_edge`, but only if both of its ends are keys of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::{EdgeError, IndexedGraph};
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// assert_eq!(graph.try_insert_edge(1, 2), Ok(()));
+    /// assert_eq!(graph.try_insert_edge(1, 3), Err(EdgeError::MissingTo(3)));
+    /// assert_eq!(graph.try_insert_edge(0, 1), Err(EdgeError::MissingFrom(0)));
+    /// assert_eq!(graph.edge_count(), 1);
+    /// ```
+    pub fn try_insert_edge(&mut self, from: K, to: K) -> Result<(), EdgeError<K>>
+    where
+        E: Default,
+    {
+        if !self.contains_key(&from) {
+            return Err(EdgeError::MissingFrom(from));
+        }
+        if !self.contains_key(&to) {
+            return Err(EdgeError::MissingTo(to));
+        }
+        self.insert_edge(from, to);
+        Ok(())
+    }
+
+    /// Inserts an edge like `insert_edge`, first inserting any end which is not a key of the graph yet,
+    /// with a default value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph: IndexedGraph<&str, u32> = IndexedGraph::new();
+    /// graph.insert_edge_auto("a", "b");
+    /// graph.insert_edge_auto("b", "c");
+    /// graph.insert_edge_auto("c", "a");
+    /// assert_eq!(graph.node_count(), 3);
+    /// assert_eq!(graph.edge_count(), 3);
+    /// assert_eq!(graph.get(&"b"), vec![&0]);
+    /// ```
+    pub fn insert_edge_auto(&mut self, from: K, to: K)
+    where
+        V: Default,
+        E: Default,
+    {
+        if !self.contains_key(&from) {
+            self.insert(from.clone(), V::default());
+        }
+        if !self.contains_key(&to) {
+            self.insert(to.clone(), V::default());
+        }
+        self.insert_edge(from, to);
+    }
+
+    /// Removes parallel edges, keeping only the first inserted edge from each source to each target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_weighted_edge(1, 2, 5);
+    /// graph.insert_weighted_edge(1, 2, 6);
+    /// graph.insert_weighted_edge(1, 2, 7);
+    /// graph.dedup_edges();
+    /// assert_eq!(graph.edge_count(), 1);
+    /// assert_eq!(graph.set_edge_weight(&1, &2, 0), Some(5));
+    /// ```
+    pub fn dedup_edges(&mut self) {
+        for targets in self.edges.values_mut() {
+            let mut seen = BTreeSet::new();
+            targets.retain(|(to, _)| seen.insert(to.clone()));
+        }
+    }
+
+    /// Removes every edge whose source or target is not a key of the graph,
+    /// as left behind by `insert_edge` or by removing nodes. Returns how many edges were removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (3, 2), (3, 1)]);
+    /// assert_eq!(graph.repair_dangling_edges(), 3);
+    /// assert_eq!(graph.edge_list(), vec![(1, 2)]);
+    /// assert_eq!(graph.repair_dangling_edges(), 0);
+    /// ```
+    pub fn repair_dangling_edges(&mut self) -> usize {
+        let before = self.edge_count();
+        let nodes = &self.i;
+        self.edges.retain(|from, targets| {
+            if !nodes.contains_key(from) {
+                return false;
+            }
+            targets.retain(|(to, _)| nodes.contains_key(to));
+            true
+        });
+        before - self.edge_count()
+    }
+
+    /// Returns all edges as owned `(from, to)` pairs, sorted by source and then in insertion
+    /// order. Parallel edges appear once per edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// graph.extend_edges(vec![(2, 3), (1, 3), (1, 2)]);
+    /// assert_eq!(graph.edge_list(), vec![(1, 3), (1, 2), (2, 3)]);
+    /// ```
+    pub fn edge_list(&self) -> Vec<(K, K)> {
+        self.edge_triples()
+            .map(|(from, to, _)| (from.clone(), to.clone()))
+            .collect()
+    }
+
+    /// Calls the closure with the source and target of every edge, without allocating.
+    /// Parallel edges are visited once per edge, in the same order as `edge_list`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.extend_edges(vec![(1, 2), (1, 2), (2, 2)]);
+    /// let mut count = 0;
+    /// let mut loops = 0;
+    /// graph.for_each_edge(|from, to| {
+    ///     count += 1;
+    ///     if from == to {
+    ///         loops += 1;
+    ///     }
+    /// });
+    /// assert_eq!((count, loops), (3, 1));
+    /// ```
+    pub fn for_each_edge<F: FnMut(&K, &K)>(&self, mut f: F) {
+        for (from, to, _) in self.edge_triples() {
+            f(from, to);
+        }
+    }
+
+    /// Gets an iterator over the sources of the edges in sorted order, each with the targets of
+    /// its edges in insertion order. Parallel edges list their target once per edge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(2, 3), (1, 3), (1, 2)]);
+    /// let adjacency: Vec<_> = graph.iter_adjacency().collect();
+    /// assert_eq!(adjacency, [(&1, vec![&3, &2]), (&2, vec![&3])]);
+    /// ```
+    pub fn iter_adjacency(&self) -> impl Iterator<Item = (&K, Vec<&K>)> + '_ {
+        self.edges
+            .iter()
+            .filter(|(_, targets)| !targets.is_empty())
+            .map(|(from, targets)| (from, targets.iter().map(|(to, _)| to).collect()))
+    }
+
+    /// Returns the number of elements in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut a = IndexedGraph::new();
+    /// assert_eq!(a.len(), 0);
+    /// a.insert(1, "a");
+    /// assert_eq!(a.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.i.len()
+    }
+
+    /// Returns `true` if the graph contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut a = IndexedGraph::new();
+    /// assert!(a.is_empty());
+    /// a.insert(1, "a");
+    /// assert!(!a.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.i.len() == 0
+    }
+
+    /// Returns the number of edges in the graph, parallel edges are counted one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// assert_eq!(graph.edge_count(), 0);
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 1);
+    /// assert_eq!(graph.edge_count(), 2);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(|targets| targets.len()).sum()
+    }
+
+    /// Returns the number of nodes in the graph, that is the number of distinct keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(1, "b");
+    /// graph.insert(2, "c");
+    /// assert_eq!(graph.node_count(), 2);
+    /// ```
+    pub fn node_count(&self) -> usize {
+        self.i.len()
+    }
+
+    /// Returns the number of edges from `from` to `to`.
+    /// Inserting the same edge again adds a parallel edge, so this can be more than one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(1, 2);
+    /// assert_eq!(graph.parallel_edge_count(&1, &2), 2);
+    /// assert_eq!(graph.parallel_edge_count(&2, &1), 0);
+    /// ```
+    pub fn parallel_edge_count(&self, from: &K, to: &K) -> usize {
+        self.successors(from).filter(|target| *target == to).count()
+    }
+
+    /// Returns `true` if any two edges share both their source and their target.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 1);
+    /// assert!(!graph.has_parallel_edges());
+    /// graph.insert_edge(1, 2);
+    /// assert!(graph.has_parallel_edges());
+    /// ```
+    pub fn has_parallel_edges(&self) -> bool {
+        self.edges.values().any(|targets| {
+            let mut seen = BTreeSet::new();
+            !targets.iter().all(|(to, _)| seen.insert(to))
+        })
+    }
+
+    /// Returns the number of edges starting from the key, parallel edges are counted one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.extend_edges(vec![(1, 2), (1, 1)]);
+    /// assert_eq!(graph.out_degree(&1), 2);
+    /// assert_eq!(graph.out_degree(&2), 0);
+    /// ```
+    pub fn out_degree(&self, key: &K) -> usize {
+        self.edges.get(key).map_or(0, |targets| targets.len())
+    }
+
+    /// Returns the number of edges pointing to the key, parallel edges are counted one by one.
+    /// This scans every edge of the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.extend_edges(vec![(1, 2), (1, 1)]);
+    /// assert_eq!(graph.in_degree(&1), 1);
+    /// assert_eq!(graph.in_degree(&2), 1);
+    /// ```
+    pub fn in_degree(&self, key: &K) -> usize {
+        self.edge_triples().filter(|(_, to, _)| *to == key).count()
+    }
+
+    /// Returns an estimate of the heap memory used by the graph, in bytes.
+    ///
+    /// This counts the capacity of the backing vectors and of the index and edge lists, and
+    /// assumes the nodes of the two `BTreeMap`s are two thirds full. Memory owned by the keys,
+    /// values or weights themselves, like the contents of a `String`, is not counted, so the
+    /// result is only approximate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(0, 0u64);
+    /// let small = graph.memory_usage();
+    /// for key in 1..1000 {
+    ///     graph.insert(key, 0u64);
+    ///     graph.insert_edge(key - 1, key);
+    /// }
+    /// assert!(graph.memory_usage() > small);
+    /// ```
+    pub fn memory_usage(&self) -> usize {
+        use std::mem::size_of;
+        fn map_bytes<K, T>(map: &BTreeMap<K, Vec<T>>) -> usize {
+            let entries = map.len() * (size_of::<K>() + size_of::<Vec<T>>()) * 3 / 2;
+            let lists: usize = map
+                .values()
+                .map(|list| list.capacity() * size_of::<T>())
+                .sum();
+            entries + lists
+        }
+        self.keys.capacity() * size_of::<K>()
+            + self.values.capacity() * size_of::<V>()
+            + map_bytes(&self.i)
+            + map_bytes(&self.edges)
+    }
+
+    pub fn index_copy(&self) -> BTreeMap<K, Vec<usize>> {
+        self.i.clone()
+    }
+
+    /// Returns a reference to the internal index, mapping each key to the positions of its values.
+    /// Prefer this over `index_copy` when the index only needs to be inspected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "c");
+    /// assert_eq!(graph.index_ref()[&1], [0, 2]);
+    /// assert_eq!(graph.index_ref().len(), 2);
+    /// ```
+    pub fn index_ref(&self) -> &BTreeMap<K, Vec<usize>> {
+        &self.i
+    }
+
+    /// Returns the positions of the values stored for the key, in insertion order.
+    /// Unlike `index_copy` this borrows from the internal index instead of cloning it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "c");
+    /// assert_eq!(graph.indexes_of(&1), &[0, 2]);
+    /// assert!(graph.indexes_of(&3).is_empty());
+    /// ```
+    pub fn indexes_of(&self, key: &K) -> &[usize] {
+        match self.i.get(key) {
+            Some(indexes) => indexes,
+            None => &[],
+        }
+    }
+
+    /// Returns a Vec of references to the values corresponding to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut a = IndexedGraph::new();
+    /// a.insert(1, "a");
+    /// assert_eq!(*a.get_by_key(1), [&"a"]);
+    /// ```
+    #[inline]
+    pub fn get_by_key(&self, key: K) -> Vec<&V> {
+        self.get(&key)
+    }
+
+    /// Returns a Vec of references to the values corresponding to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut a = IndexedGraph::new();
+    /// a.insert(1, "a");
+    /// #[allow(deprecated)]
+    /// let values = a.index(1);
+    /// assert_eq!(values, a.get_by_key(1));
+    /// ```
+    #[deprecated(
+        since = "0.1.2",
+        note = "`index` looks positional but does a key lookup, use `get_by_key` instead"
+    )]
+    #[inline]
+    pub fn index(&self, key: K) -> Vec<&V> {
+        self.get_by_key(key)
+    }
+
+    /// Gets an iterator over the entries of the graph, sorted by key.
+    /// `IndexedGraph` preserves the order of insertion for `iter()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(3, "c");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "a");
+    ///
+    /// for (key, value) in graph.iter() {
+    ///     println!("{}: {}", key, value);
+    /// }
+    ///
+    /// let (first_key, first_value) = graph.iter().next().unwrap();
+    /// assert_eq!((*first_key, *first_value), (3, "c"));
+    ///
+    /// // every value of a duplicate key is yielded, not just one entry per key
+    /// graph.insert(3, "d");
+    /// assert_eq!(graph.len(), 3);
+    /// assert_eq!(graph.iter().len(), 4);
+    /// assert_eq!(graph.iter().last(), Some((&3, &"d")));
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V, E> {
+        Iter {
+            graph: self,
+            length: self.keys.len(),
+        }
+    }
+
+    /// Gets an iterator over the entries of the graph, from the last inserted to the first.
+    /// This is the same as `iter().rev()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// let keys: Vec<_> = graph.iter_rev().map(|(key, _)| *key).collect();
+    /// assert_eq!(keys, [3, 2, 1]);
+    /// ```
+    pub fn iter_rev(&self) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.iter().rev()
+    }
+
+    /// Gets an iterator over the entries whose value satisfies `pred`, in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "apple");
+    /// graph.insert(2, "banana");
+    /// graph.insert(3, "avocado");
+    /// let keys: Vec<_> = graph
+    ///     .iter_values_where(|value| value.starts_with('a'))
+    ///     .map(|(key, _)| *key)
+    ///     .collect();
+    /// assert_eq!(keys, [1, 3]);
+    /// ```
+    pub fn iter_values_where<'a, F: Fn(&V) -> bool + 'a>(
+        &'a self,
+        pred: F,
+    ) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        self.iter().filter(move |(_, value)| pred(value))
+    }
+
+    /// Gets an iterator over the entries whose key satisfies `pred`, in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(5, "e");
+    /// graph.insert(1, "a");
+    /// graph.insert(9, "i");
+    /// graph.insert(3, "c");
+    /// let values: Vec<_> = graph
+    ///     .iter_keys_where(|key| *key > 2)
+    ///     .map(|(_, value)| *value)
+    ///     .collect();
+    /// assert_eq!(values, ["e", "i", "c"]);
+    /// ```
+    pub fn iter_keys_where<'a, F: Fn(&K) -> bool + 'a>(
+        &'a self,
+        pred: F,
+    ) -> impl Iterator<Item = (&'a K, &'a V)> + 'a {
+        self.iter().filter(move |(key, _)| pred(key))
+    }
+
+    /// Returns the first entry in insertion order for which `pred` returns `true`,
+    /// without looking at the entries after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "b");
+    /// assert_eq!(graph.find(|_, value| *value == "b"), Some((&2, &"b")));
+    /// assert_eq!(graph.find(|key, _| *key > 3), None);
+    /// ```
+    pub fn find<F: FnMut(&K, &V) -> bool>(&self, mut pred: F) -> Option<(&K, &V)> {
+        self.keys
+            .iter()
+            .zip(&self.values)
+            .find(|(key, value)| pred(key, value))
+    }
+
+    /// Folds every value of the graph into an accumulator, in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert("a", 1);
+    /// graph.insert("b", 2);
+    /// graph.insert("a", 3);
+    /// assert_eq!(graph.fold_values(0, |sum, value| sum + value), 6);
+    /// ```
+    pub fn fold_values<B, F: FnMut(B, &V) -> B>(&self, init: B, f: F) -> B {
+        self.values.iter().fold(init, f)
+    }
+
+    /// Consumes the graph, passing every entry through `f` and keeping the returned ones in insertion order.
+    /// Edges are kept only when both of their ends are keys of the new graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "");
+    /// graph.insert(3, "c");
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(1, 3);
+    ///
+    /// let graph = graph.filter_map_values(|key, value| {
+    ///     if value.is_empty() {
+    ///         None
+    ///     } else {
+    ///         Some((key, value.to_uppercase()))
+    ///     }
+    /// });
+    /// assert_eq!(graph.get(&1), vec![&"A".to_string()]);
+    /// assert!(!graph.contains_key(&2));
+    /// assert_eq!(graph.edge_count(), 1);
+    /// ```
+    pub fn filter_map_values<W, F: FnMut(K, V) -> Option<(K, W)>>(
+        self,
+        mut f: F,
+    ) -> IndexedGraph<K, W, E> {
+        let mut graph = IndexedGraph::new_weighted();
+        for (key, value) in self.keys.into_iter().zip(self.values) {
+            if let Some((key, value)) = f(key, value) {
+                graph.insert(key, value);
+            }
+        }
+        for (from, targets) in self.edges {
+            if !graph.contains_key(&from) {
+                continue;
+            }
+            for (to, weight) in targets {
+                if graph.contains_key(&to) {
+                    graph.insert_weighted_edge(from.clone(), to, weight);
+                }
+            }
+        }
+        graph
+    }
+
+    /// Gets an iterator over the entries of the graph in insertion order,
+    /// together with the position each entry is stored at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(3, "c");
+    /// graph.insert(1, "a");
+    /// graph.insert(3, "d");
+    ///
+    /// let entries: Vec<_> = graph.iter_indexed().collect();
+    /// assert_eq!(entries, [(0, &3, &"c"), (1, &1, &"a"), (2, &3, &"d")]);
+    /// assert_eq!(graph.indexes_of(&3), &[0, 2]);
+    /// ```
+    pub fn iter_indexed(&self) -> impl Iterator<Item = (usize, &K, &V)> + '_ {
+        self.keys
+            .iter()
+            .zip(&self.values)
+            .enumerate()
+            .map(|(idx, (key, value))| (idx, key, value))
+    }
+
+    /// Returns the entry with the greatest value according to `compare`, or `None` if the graph is empty.
+    /// If several entries are equally great, the last inserted one is returned, like `Iterator::max_by`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "ab");
+    /// graph.insert(2, "abcd");
+    /// graph.insert(3, "abc");
+    /// assert_eq!(graph.max_by_value(|a, b| a.len().cmp(&b.len())), Some((&2, &"abcd")));
+    /// ```
+    pub fn max_by_value<F: FnMut(&V, &V) -> Ordering>(&self, mut compare: F) -> Option<(&K, &V)> {
+        self.iter().max_by(|a, b| compare(a.1, b.1))
+    }
+
+    /// Returns the entry with the smallest value according to `compare`, or `None` if the graph is empty.
+    /// If several entries are equally small, the first inserted one is returned, like `Iterator::min_by`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "ab");
+    /// graph.insert(2, "abcd");
+    /// graph.insert(3, "cd");
+    /// assert_eq!(graph.min_by_value(|a, b| a.len().cmp(&b.len())), Some((&1, &"ab")));
+    /// ```
+    pub fn min_by_value<F: FnMut(&V, &V) -> Ordering>(&self, mut compare: F) -> Option<(&K, &V)> {
+        self.iter().min_by(|a, b| compare(a.1, b.1))
+    }
+
+    /// Returns every distinct key with all of its values, sorted by key.
+    /// The values of a key are in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "c");
+    ///
+    /// let grouped = graph.grouped();
+    /// assert_eq!(grouped.len(), 2);
+    /// assert_eq!(grouped[&2], [&"b", &"c"]);
+    /// assert_eq!(grouped.into_iter().next(), Some((&1, vec![&"a"])));
+    /// ```
+    pub fn grouped(&self) -> BTreeMap<&K, Vec<&V>> {
+        self.i
+            .iter()
+            .map(|(key, indexes)| (key, indexes.iter().map(|idx| &self.values[*idx]).collect()))
+            .collect()
+    }
+
+    /// Returns a graph with keys and values swapped, in the same insertion order.
+    /// Equal values become a key with many values. The edges are dropped,
+    /// since they connect keys which are values in the new graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "a");
+    /// graph.insert_edge(1, 2);
+    ///
+    /// let inverted = graph.invert();
+    /// assert_eq!(inverted.get(&"a"), vec![&1, &3]);
+    /// assert_eq!(inverted.get(&"b"), vec![&2]);
+    /// assert_eq!(inverted.edge_count(), 0);
+    /// ```
+    pub fn invert(&self) -> IndexedGraph<V, K>
+    where
+        V: Ord + Clone,
+    {
+        let mut inverted = IndexedGraph::new();
+        for (key, value) in self.keys.iter().zip(&self.values) {
+            inverted.insert(value.clone(), key.clone());
+        }
+        inverted
+    }
+
+    /// Returns the last `n` inserted entries, in insertion order.
+    /// Returns all entries if the graph holds fewer than `n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for (key, value) in (1..=5).zip("abcde".chars()) {
+    ///     graph.insert(key, value);
+    /// }
+    /// assert_eq!(graph.recent(2), [(&4, &'d'), (&5, &'e')]);
+    /// assert_eq!(graph.recent(10).len(), 5);
+    /// ```
+    pub fn recent(&self, n: usize) -> Vec<(&K, &V)> {
+        let start = self.keys.len().saturating_sub(n);
+        self.keys[start..]
+            .iter()
+            .zip(&self.values[start..])
+            .collect()
+    }
+
+    /// Gets an iterator over the entries whose key falls in the range, sorted by key.
+    /// Values of the same key are yielded in insertion order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(3, "c");
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    ///
+    /// let found: Vec<_> = graph.range(2..).collect();
+    /// assert_eq!(found, [(&2, &"b"), (&3, &"c")]);
+    /// ```
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.i.range(range).flat_map(move |(key, indexes)| {
+            indexes.iter().map(move |idx| (key, &self.values[*idx]))
+        })
+    }
+
+    /// Gets an iterator over the entries with keys between `low` and `high` inclusive, sorted by key.
+    ///
+    /// With tuple keys like `(group, id)` this retrieves every entry of a group,
+    /// by passing the smallest and largest possible key of the group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert((2, 1), "c");
+    /// graph.insert((1, 7), "b");
+    /// graph.insert((1, 3), "a");
+    /// graph.insert((3, 0), "d");
+    ///
+    /// let group: Vec<_> = graph
+    ///     .range_prefix((1, u32::MIN), (1, u32::MAX))
+    ///     .map(|(_k, v)| *v)
+    ///     .collect();
+    /// assert_eq!(group, ["a", "b"]);
+    /// ```
+    pub fn range_prefix(&self, low: K, high: K) -> impl Iterator<Item = (&K, &V)> + '_ {
+        self.range(low..=high)
+    }
+
+    /// Merges the values of every duplicate key into one, folding them in insertion order with `combine`.
+    /// The merged entry takes the place of the first entry of its key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, 10);
+    /// graph.insert(2, 5);
+    /// graph.insert(1, 20);
+    /// graph.coalesce(|a, b| a + b);
+    /// assert_eq!(graph.get(&1), vec![&30]);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&1, &30), (&2, &5)]);
+    /// ```
+    pub fn coalesce<F: FnMut(V, V) -> V>(&mut self, mut combine: F) {
+        let keys = std::mem::take(&mut self.keys);
+        let mut values: Vec<Option<V>> = self.values.drain(..).map(Some).collect();
+        for (pos, key) in keys.into_iter().enumerate() {
+            let indexes = &self.i[&key];
+            if indexes[0] != pos {
+                continue;
+            }
+            let mut merged = values[pos].take().unwrap();
+            for idx in &indexes[1..] {
+                merged = combine(merged, values[*idx].take().unwrap());
+            }
+            self.keys.push(key);
+            self.values.push(merged);
+        }
+        self.rebuild_index();
+    }
+
+    /// Replaces every value of the key with the given ones, returning the old values in insertion order.
+    /// The new values are inserted after all other entries, like `insert`.
+    /// Passing no values removes the key, its edges are left as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(1, "c");
+    /// graph.insert(1, "d");
+    /// assert_eq!(graph.replace_all(&1, vec!["e"]), ["a", "c", "d"]);
+    /// assert_eq!(graph.get(&1), vec![&"e"]);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&2, &"b"), (&1, &"e")]);
+    ///
+    /// assert_eq!(graph.replace_all(&1, vec![]), ["e"]);
+    /// assert!(!graph.contains_key(&1));
+    /// ```
+    pub fn replace_all(&mut self, key: &K, values: Vec<V>) -> Vec<V> {
+        let old = self
+            .remove_entries(|_, stored| stored == key)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        for value in values {
+            self.insert(key.clone(), value);
+        }
+        old
+    }
+
+    /// Removes every node without any edge, neither starting from it nor pointing to it.
+    /// All values of those keys are removed, and the removed keys are returned in sorted order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// graph.insert(3, "d");
+    /// graph.insert_edge(1, 2);
+    /// assert_eq!(graph.remove_isolated(), [3]);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&1, &"a"), (&2, &"b")]);
+    /// ```
+    pub fn remove_isolated(&mut self) -> Vec<K> {
+        let mut connected = BTreeSet::new();
+        for (from, to, _) in self.edge_triples() {
+            connected.insert(from.clone());
+            connected.insert(to.clone());
+        }
+        let isolated: Vec<K> = self
+            .i
+            .keys()
+            .filter(|key| !connected.contains(*key))
+            .cloned()
+            .collect();
+        for key in &isolated {
+            self.edges.remove(key);
+        }
+        self.remove_entries(|_, key| !connected.contains(key));
+        isolated
+    }
+
+    /// Keeps only the keys for which the predicate returns `true`, removing all values of the
+    /// other keys. The predicate is called once per distinct key, in sorted order.
+    /// Edges starting from or pointing to a removed key are removed as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, key * 10);
+    /// }
+    /// graph.insert(2, 21);
+    /// graph.extend_edges(vec![(1, 2), (2, 4), (4, 3), (4, 2)]);
+    /// graph.retain_keys(|key| key % 2 == 0);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&2, &20), (&4, &40), (&2, &21)]);
+    /// assert_eq!(graph.edge_list(), vec![(2, 4), (4, 2)]);
+    /// ```
+    pub fn retain_keys<F: FnMut(&K) -> bool>(&mut self, mut f: F) {
+        let removed: BTreeSet<K> = self.i.keys().filter(|key| !f(key)).cloned().collect();
+        if removed.is_empty() {
+            return;
+        }
+        self.edges.retain(|from, targets| {
+            targets.retain(|(to, _)| !removed.contains(to));
+            !removed.contains(from)
+        });
+        self.remove_entries(|_, key| removed.contains(key));
+    }
+
+    /// Removes all values of every listed key in a single pass, rebuilding the index once.
+    /// Edges starting from or pointing to a removed key are removed as well.
+    /// Keys which aren't in the graph are ignored. Returns the number of entries removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, key * 10);
+    /// }
+    /// graph.insert(2, 21);
+    /// graph.extend_edges(vec![(1, 2), (3, 4), (4, 5)]);
+    /// assert_eq!(graph.remove_all(&[2, 4, 5, 6]), 4);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&1, &10), (&3, &30)]);
+    /// assert_eq!(graph.indexes_of(&3), [1]);
+    /// assert_eq!(graph.edge_count(), 0);
+    /// ```
+    pub fn remove_all(&mut self, keys: &[K]) -> usize {
+        let removed: BTreeSet<&K> = keys.iter().filter(|key| self.i.contains_key(key)).collect();
+        if removed.is_empty() {
+            return 0;
+        }
+        self.edges.retain(|from, targets| {
+            targets.retain(|(to, _)| !removed.contains(to));
+            !removed.contains(from)
+        });
+        self.remove_entries(|_, key| removed.contains(key)).len()
+    }
+
+    /// Moves the entries and edges of `other` into the graph.
+    /// When a key has exactly one value in both graphs, `resolve` is called with the key, the
+    /// value of this graph and the value of `other`, and its result replaces the value in place.
+    /// The values of every other key of `other` are inserted after the existing entries,
+    /// like `insert`. An edge of `other` is added unless the graph already has an edge between
+    /// the same keys.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, 10);
+    /// graph.insert(2, 20);
+    /// graph.insert_edge(1, 2);
+    /// let mut other = IndexedGraph::new();
+    /// other.insert(2, 5);
+    /// other.insert(3, 30);
+    /// other.extend_edges(vec![(1, 2), (2, 3)]);
+    ///
+    /// let mut resolved = vec![];
+    /// graph.merge_with(other, |key, ours, theirs| {
+    ///     resolved.push(*key);
+    ///     ours + theirs
+    /// });
+    /// assert_eq!(resolved, [2]);
+    /// assert_eq!(graph.iter().collect::<Vec<_>>(), [(&1, &10), (&2, &25), (&3, &30)]);
+    /// assert_eq!(graph.edge_list(), vec![(1, 2), (2, 3)]);
+    /// ```
+    pub fn merge_with<F: FnMut(&K, V, V) -> V>(
+        &mut self,
+        other: IndexedGraph<K, V, E>,
+        mut resolve: F,
+    ) {
+        let IndexedGraph {
+            keys,
+            values,
+            edges,
+            i,
+            ..
+        } = other;
+        let mut merged: Vec<Option<V>> = std::mem::take(&mut self.values)
+            .into_iter()
+            .map(Some)
+            .collect();
+        let mut appended = vec![];
+        for (key, value) in keys.into_iter().zip(values) {
+            match self.i.get(&key).map(Vec::as_slice) {
+                Some([idx]) if i[&key].len() == 1 => {
+                    let ours = merged[*idx].take().unwrap();
+                    merged[*idx] = Some(resolve(&key, ours, value));
+                }
+                _ => appended.push((key, value)),
+            }
+        }
+        self.values = merged.into_iter().map(Option::unwrap).collect();
+        for (key, value) in appended {
+            self.insert(key, value);
+        }
+        for (from, targets) in edges {
+            for (to, weight) in targets {
+                if self.parallel_edge_count(&from, &to) == 0 {
+                    self.insert_weighted_edge(from.clone(), to, weight);
+                }
+            }
+        }
+    }
+
+    /// Reorders the entries of the graph by key.
+    /// The sort is stable, values of a duplicate key keep their relative order.
+    /// Afterwards `iter()` yields the entries in sorted key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(3, "c");
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.sort_by_key();
+    ///
+    /// let keys: Vec<_> = graph.iter().map(|(k, _v)| *k).collect();
+    /// assert_eq!(keys, [1, 2, 3]);
+    /// assert_eq!(graph.get(&3), vec![&"c"]);
+    /// ```
+    pub fn sort_by_key(&mut self) {
+        let mut entries: Vec<(K, V)> = self.keys.drain(..).zip(self.values.drain(..)).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (key, value) in entries {
+            self.keys.push(key);
+            self.values.push(value);
+        }
+        self.rebuild_index();
+    }
+
+    /// Returns the path with the fewest edges from `from` to `to`, including both ends.
+    /// Found by breadth-first search, edge weights are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(3, 4);
+    /// graph.insert_edge(1, 3);
+    /// assert_eq!(graph.shortest_path(&1, &4), Some(vec![1, 3, 4]));
+    /// assert_eq!(graph.shortest_path(&4, &1), None);
+    /// ```
+    pub fn shortest_path(&self, from: &K, to: &K) -> Option<Vec<K>> {
+        self.shortest_path_avoiding(from, to, &BTreeSet::new(), &BTreeSet::new())
+    }
+
+    /// Returns up to `k` loopless paths from `from` to `to`, shortest first.
+    /// Uses Yen's algorithm on top of the breadth-first `shortest_path`, so paths are ranked
+    /// by their number of edges and edge weights are ignored. Paths of equal length come in
+    /// the order they are found. If there are fewer than `k` such paths, all of them are returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 4);
+    /// graph.insert_edge(1, 3);
+    /// graph.insert_edge(3, 5);
+    /// graph.insert_edge(5, 4);
+    /// let paths = graph.k_shortest_paths(&1, &4, 3);
+    /// assert_eq!(paths, vec![vec![1, 2, 4], vec![1, 3, 5, 4]]);
+    /// ```
+    pub fn k_shortest_paths(&self, from: &K, to: &K, k: usize) -> Vec<Vec<K>> {
+        let mut paths = vec![];
+        if k == 0 {
+            return paths;
+        }
+        match self.shortest_path(from, to) {
+            Some(path) => paths.push(path),
+            None => return paths,
+        }
+        let mut candidates: Vec<Vec<K>> = vec![];
+        while paths.len() < k {
+            let previous = paths.last().unwrap().clone();
+            for spur in 0..previous.len() - 1 {
+                let root = &previous[..=spur];
+                let mut removed_edges = BTreeSet::new();
+                for path in &paths {
+                    if path.len() > spur + 1 && &path[..=spur] == root {
+                        removed_edges.insert((path[spur].clone(), path[spur + 1].clone()));
+                    }
+                }
+                let removed_nodes = root[..spur].iter().cloned().collect();
+                if let Some(spur_path) =
+                    self.shortest_path_avoiding(&root[spur], to, &removed_nodes, &removed_edges)
+                {
+                    let mut candidate = root[..spur].to_vec();
+                    candidate.extend(spur_path);
+                    if !candidates.contains(&candidate) && !paths.contains(&candidate) {
+                        candidates.push(candidate);
+                    }
+                }
+            }
+            if candidates.is_empty() {
+                break;
+            }
+            let mut best = 0;
+            for (idx, candidate) in candidates.iter().enumerate() {
+                if candidate.len() < candidates[best].len() {
+                    best = idx;
+                }
+            }
+            paths.push(candidates.remove(best));
+        }
+        paths
+    }
+
+    /// Breadth-first search for the shortest path which doesn't touch the removed nodes and edges.
+    fn shortest_path_avoiding(
+        &self,
+        from: &K,
+        to: &K,
+        removed_nodes: &BTreeSet<K>,
+        removed_edges: &BTreeSet<(K, K)>,
+    ) -> Option<Vec<K>> {
+        if removed_nodes.contains(from) {
+            return None;
+        }
+        let mut parents: BTreeMap<K, K> = BTreeMap::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(from.clone());
+        queue.push_back(from.clone());
+        while let Some(node) = queue.pop_front() {
+            if &node == to {
+                let mut path = vec![node];
+                while let Some(parent) = parents.get(path.last().unwrap()) {
+                    path.push(parent.clone());
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for next in self.successors(&node) {
+                if visited.contains(next)
+                    || removed_nodes.contains(next)
+                    || (!removed_edges.is_empty()
+                        && removed_edges.contains(&(node.clone(), next.clone())))
+                {
+                    continue;
+                }
+                visited.insert(next.clone());
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next.clone());
+            }
+        }
+        None
+    }
+
+    /// Collects the neighbors of every node, treating edges as undirected.
+    /// Only keys present in the graph take part, self loops are left out.
+    fn undirected_adjacency(&self) -> BTreeMap<&K, BTreeSet<&K>> {
+        let mut adjacency: BTreeMap<&K, BTreeSet<&K>> =
+            self.i.keys().map(|key| (key, BTreeSet::new())).collect();
+        for (from, targets) in &self.edges {
+            for (to, _) in targets {
+                if from == to || !self.i.contains_key(from) || !self.i.contains_key(to) {
+                    continue;
+                }
+                adjacency.get_mut(from).unwrap().insert(to);
+                adjacency.get_mut(to).unwrap().insert(from);
+            }
+        }
+        adjacency
+    }
+
+    /// Iterates the targets of the edges starting from the key.
+    fn successors(&self, key: &K) -> impl Iterator<Item = &K> + '_ {
+        self.edges.get(key).into_iter().flatten().map(|(to, _)| to)
+    }
+
+    /// Returns the weakly connected components of the graph, treating edges as undirected.
+    /// Each component lists its keys in sorted order, components are ordered by their smallest key.
+    /// Edges pointing to keys which aren't in the graph are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(2, 1);
+    /// graph.insert_edge(4, 3);
+    /// graph.insert_edge(5, 3);
+    /// assert_eq!(graph.connected_components(), vec![vec![1, 2], vec![3, 4, 5]]);
+    /// ```
+    pub fn connected_components(&self) -> Vec<Vec<K>> {
+        let adjacency = self.undirected_adjacency();
+        let mut seen = BTreeSet::new();
+        let mut components = vec![];
+        for start in self.i.keys() {
+            if !seen.insert(start) {
+                continue;
+            }
+            let mut component = vec![];
+            let mut queue = VecDeque::new();
+            queue.push_back(start);
+            while let Some(node) = queue.pop_front() {
+                component.push(node.clone());
+                for next in &adjacency[node] {
+                    if seen.insert(*next) {
+                        queue.push_back(*next);
+                    }
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// Splits the graph into its weakly connected components, each as a standalone graph.
+    /// Every component keeps its entries in insertion order and the edges between its nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// graph.insert(4, "d");
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(4, 3);
+    ///
+    /// let parts = graph.components_as_graphs();
+    /// assert_eq!(parts.len(), 2);
+    /// assert_eq!(parts[0].get(&2), vec![&"b"]);
+    /// assert_eq!(parts[0].shortest_path(&1, &2), Some(vec![1, 2]));
+    /// assert!(!parts[1].contains_key(&1));
+    /// assert_eq!(parts[1].shortest_path(&4, &3), Some(vec![4, 3]));
+    /// ```
+    pub fn components_as_graphs(&self) -> Vec<IndexedGraph<K, V, E>>
+    where
+        V: Clone,
+        E: Clone,
+    {
+        let mut component_of = BTreeMap::new();
+        let mut graphs = vec![];
+        for (idx, component) in self.connected_components().into_iter().enumerate() {
+            for key in component {
+                component_of.insert(key, idx);
+            }
+            graphs.push(IndexedGraph::new_weighted());
+        }
+        for (key, value) in self.keys.iter().zip(&self.values) {
+            graphs[component_of[key]].insert(key.clone(), value.clone());
+        }
+        for (from, targets) in &self.edges {
+            if let Some(idx) = component_of.get(from) {
+                for (to, weight) in targets {
+                    if component_of.get(to) == Some(idx) {
+                        graphs[*idx].insert_weighted_edge(from.clone(), to.clone(), weight.clone());
+                    }
+                }
+            }
+        }
+        graphs
+    }
+
+    /// Returns every key which can reach `key` by following edges, not including `key` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(3, 4);
+    /// assert_eq!(graph.ancestors(&3).into_iter().collect::<Vec<_>>(), [1, 2]);
+    /// assert!(graph.ancestors(&1).is_empty());
+    /// ```
+    pub fn ancestors(&self, key: &K) -> BTreeSet<K> {
+        let reverse = self.reverse_adjacency();
+        let mut found = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(key);
+        while let Some(node) = queue.pop_front() {
+            for prev in reverse.get(node).into_iter().flatten() {
+                if found.insert((*prev).clone()) {
+                    queue.push_back(*prev);
+                }
+            }
+        }
+        found.remove(key);
+        found
+    }
+
+    /// Returns every key reachable from `key` by following edges, not including `key` itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(3, 4);
+    /// assert_eq!(graph.descendants(&2).into_iter().collect::<Vec<_>>(), [3, 4]);
+    /// assert!(graph.descendants(&4).is_empty());
+    /// ```
+    pub fn descendants(&self, key: &K) -> BTreeSet<K> {
+        let mut found = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        queue.push_back(key);
+        while let Some(node) = queue.pop_front() {
+            for next in self.successors(node) {
+                if found.insert(next.clone()) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        found.remove(key);
+        found
+    }
+
+    /// Returns the number of keys reachable from `start` by following edges, not including
+    /// `start` itself. This is `descendants(start).len()`, but only borrows the keys it visits
+    /// instead of cloning them into a set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4)]);
+    /// assert_eq!(graph.reachable_count(&1), 3);
+    /// assert_eq!(graph.reachable_count(&3), 1);
+    /// assert_eq!(graph.reachable_count(&4), 0);
+    /// graph.insert_edge(4, 1);
+    /// assert_eq!(graph.reachable_count(&3), 3);
+    /// ```
+    pub fn reachable_count(&self, start: &K) -> usize {
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(start);
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            for next in self.successors(node) {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        seen.len() - 1
+    }
+
+    /// Returns the mother vertices of the graph in sorted order: the keys from which every other
+    /// key can be reached by following edges. Edges pointing to keys which aren't in the graph
+    /// are ignored.
+    ///
+    /// The key whose depth-first search starts last is the only candidate worth checking.
+    /// If it reaches everything, the mother vertices are exactly the keys reaching it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(3, 1), (1, 2), (2, 4), (3, 5)]);
+    /// assert_eq!(graph.mother_vertices(), [3]);
+    /// graph.insert_edge(4, 3);
+    /// assert_eq!(graph.mother_vertices(), [1, 2, 3, 4]);
+    /// graph.insert(6, ());
+    /// assert!(graph.mother_vertices().is_empty());
+    /// ```
+    pub fn mother_vertices(&self) -> Vec<K> {
+        fn search<'a, K: Ord>(
+            adjacency: &BTreeMap<&'a K, BTreeSet<&'a K>>,
+            start: &'a K,
+            seen: &mut BTreeSet<&'a K>,
+        ) {
+            let mut stack = vec![start];
+            seen.insert(start);
+            while let Some(node) = stack.pop() {
+                for next in adjacency.get(node).into_iter().flatten() {
+                    if seen.insert(*next) {
+                        stack.push(*next);
+                    }
+                }
+            }
+        }
+        let adjacency = self.directed_adjacency();
+        let mut seen = BTreeSet::new();
+        let mut candidate = None;
+        for key in adjacency.keys() {
+            if !seen.contains(key) {
+                candidate = Some(*key);
+                search(&adjacency, key, &mut seen);
+            }
+        }
+        let candidate = match candidate {
+            Some(candidate) => candidate,
+            None => return vec![],
+        };
+        let mut reached = BTreeSet::new();
+        search(&adjacency, candidate, &mut reached);
+        if reached.len() < adjacency.len() {
+            return vec![];
+        }
+        let mut reverse: BTreeMap<&K, BTreeSet<&K>> = BTreeMap::new();
+        for (from, targets) in &adjacency {
+            for to in targets {
+                reverse.entry(*to).or_default().insert(*from);
+            }
+        }
+        let mut mothers = BTreeSet::new();
+        search(&reverse, candidate, &mut mothers);
+        mothers.into_iter().cloned().collect()
+    }
+
+    /// Returns the strongly connected components of the graph, using Tarjan's algorithm.
+    /// Each component lists its keys in sorted order.
+    /// Components come in reverse topological order, a component is listed before the ones reaching it.
+    /// Edges pointing to keys which aren't in the graph are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 1);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(3, 4);
+    /// graph.insert_edge(4, 3);
+    /// assert_eq!(graph.strongly_connected_components(), vec![vec![3, 4], vec![1, 2]]);
+    /// ```
+    pub fn strongly_connected_components(&self) -> Vec<Vec<K>> {
+        let successors = |key: &K| -> Vec<&K> {
+            self.successors(key)
+                .filter(|to| self.i.contains_key(to))
+                .collect()
+        };
+        let mut order: BTreeMap<&K, usize> = BTreeMap::new();
+        let mut low: BTreeMap<&K, usize> = BTreeMap::new();
+        let mut stack = vec![];
+        let mut on_stack = BTreeSet::new();
+        let mut components = vec![];
+        for root in self.i.keys() {
+            if order.contains_key(root) {
+                continue;
+            }
+            order.insert(root, order.len());
+            low.insert(root, order[root]);
+            stack.push(root);
+            on_stack.insert(root);
+            // Iterative depth-first search, to not overflow the call stack on long paths.
+            let mut calls = vec![(root, successors(root), 0)];
+            while let Some((node, targets, pos)) = calls.last_mut() {
+                let node = *node;
+                let next = targets.get(*pos).copied();
+                *pos += 1;
+                if let Some(next) = next {
+                    if !order.contains_key(next) {
+                        order.insert(next, order.len());
+                        low.insert(next, order[next]);
+                        stack.push(next);
+                        on_stack.insert(next);
+                        calls.push((next, successors(next), 0));
+                    } else if on_stack.contains(next) {
+                        let lowest = low[node].min(order[next]);
+                        low.insert(node, lowest);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some((parent, _, _)) = calls.last() {
+                    let lowest = low[parent].min(low[node]);
+                    low.insert(parent, lowest);
+                }
+                if low[node] == order[node] {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack.remove(member);
+                        component.push(member.clone());
+                        if member == node {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+
+    /// Returns the condensation of the graph, collapsing each strongly connected component into a node.
+    /// The nodes are numbered in topological order and carry the keys of their component.
+    /// The result is acyclic, parallel edges between components are merged into one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(3, 1);
+    /// graph.insert_edge(3, 4);
+    /// graph.insert_edge(2, 4);
+    ///
+    /// let dag = graph.condensation();
+    /// assert_eq!(dag.len(), 2);
+    /// assert_eq!(dag.get(&0), [&vec![1, 2, 3]]);
+    /// assert_eq!(dag.get(&1), [&vec![4]]);
+    /// assert_eq!(dag.k_shortest_paths(&0, &1, 2), vec![vec![0, 1]]);
+    /// ```
+    pub fn condensation(&self) -> IndexedGraph<usize, Vec<K>> {
+        let mut components = self.strongly_connected_components();
+        components.reverse();
+        let mut component_of = BTreeMap::new();
+        for (idx, component) in components.iter().enumerate() {
+            for key in component {
+                component_of.insert(key, idx);
+            }
+        }
+        let mut edges = BTreeSet::new();
+        for (from, targets) in &self.edges {
+            if let Some(source) = component_of.get(from) {
+                for (to, _) in targets {
+                    match component_of.get(to) {
+                        Some(target) if target != source => {
+                            edges.insert((*source, *target));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        let mut dag = IndexedGraph::new();
+        for (idx, component) in components.iter().enumerate() {
+            dag.insert(idx, component.clone());
+        }
+        for (source, target) in edges {
+            dag.insert_edge(source, target);
+        }
+        dag
+    }
+
+    /// Maps the keys of a path, like the one from `shortest_path`, to their first value.
+    /// Keys which aren't in the graph map to `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert(3, "c");
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    ///
+    /// let path = graph.shortest_path(&1, &3).unwrap();
+    /// assert_eq!(graph.path_values(&path), [Some(&"a"), Some(&"b"), Some(&"c")]);
+    /// assert_eq!(graph.path_values(&[1, 4]), [Some(&"a"), None]);
+    /// ```
+    pub fn path_values(&self, path: &[K]) -> Vec<Option<&V>> {
+        path.iter()
+            .map(|key| {
+                self.i
+                    .get(key)
+                    .and_then(|indexes| indexes.first())
+                    .map(|idx| &self.values[*idx])
+            })
+            .collect()
+    }
+
+    /// Returns the articulation points of the graph, treating edges as undirected, in sorted order.
+    /// Removing an articulation point splits its connected component into more components.
+    /// Uses the depth-first search low-link algorithm.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// // two triangles sharing the node 3
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (3, 4), (4, 5), (5, 3)]);
+    /// assert_eq!(graph.articulation_points(), [3]);
+    ///
+    /// graph.insert(6, ());
+    /// graph.insert_edge(5, 6);
+    /// assert_eq!(graph.articulation_points(), [3, 5]);
+    /// ```
+    pub fn articulation_points(&self) -> Vec<K> {
+        self.low_link().0.into_iter().collect()
+    }
+
+    /// Returns the bridges of the graph, treating edges as undirected, in sorted order.
+    /// Removing a bridge splits its connected component in two.
+    /// Each bridge is reported once, as a pair with the smaller key first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=6 {
+    ///     graph.insert(key, ());
+    /// }
+    /// // two triangles connected by the edge between 3 and 4
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (4, 5), (5, 6), (6, 4), (4, 3)]);
+    /// assert_eq!(graph.bridges(), [(3, 4)]);
+    /// ```
+    pub fn bridges(&self) -> Vec<(K, K)> {
+        self.low_link().1.into_iter().collect()
+    }
+
+    /// Returns `true` if the graph is bipartite, treating edges as undirected.
+    /// See `two_coloring`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4), (4, 1)]);
+    /// assert!(graph.is_bipartite());
+    ///
+    /// graph.insert(5, ());
+    /// graph.extend_edges(vec![(4, 5), (5, 1)]);
+    /// assert!(!graph.is_bipartite());
+    ///
+    /// let mut looped = IndexedGraph::new();
+    /// looped.insert(1, ());
+    /// looped.insert_edge(1, 1);
+    /// assert!(!looped.is_bipartite());
+    /// ```
+    pub fn is_bipartite(&self) -> bool {
+        self.two_coloring().is_some()
+    }
+
+    /// Colors the nodes with two colors so no edge joins nodes of the same color, treating edges as undirected.
+    /// Every connected component is colored separately by breadth-first search,
+    /// its smallest key gets `false`.
+    /// Returns `None` if there is no such coloring, because of an odd cycle.
+    /// A self loop is an odd cycle of length one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3)]);
+    /// let colors = graph.two_coloring().unwrap();
+    /// assert_eq!(colors.into_iter().collect::<Vec<_>>(), [(1, false), (2, true), (3, false)]);
+    ///
+    /// graph.insert_edge(3, 1);
+    /// assert_eq!(graph.two_coloring(), None);
+    ///
+    /// let mut looped = IndexedGraph::new();
+    /// looped.insert(1, ());
+    /// looped.insert_edge(1, 1);
+    /// assert_eq!(looped.two_coloring(), None);
+    /// ```
+    pub fn two_coloring(&self) -> Option<BTreeMap<K, bool>> {
+        // undirected_adjacency leaves self loops out, but they can't be colored either
+        if self
+            .edge_triples()
+            .any(|(from, to, _)| from == to && self.i.contains_key(from))
+        {
+            return None;
+        }
+        let adjacency = self.undirected_adjacency();
+        let mut colors: BTreeMap<&K, bool> = BTreeMap::new();
+        for start in adjacency.keys() {
+            if colors.contains_key(start) {
+                continue;
+            }
+            colors.insert(start, false);
+            let mut queue = VecDeque::new();
+            queue.push_back(*start);
+            while let Some(node) = queue.pop_front() {
+                let color = colors[node];
+                for next in &adjacency[node] {
+                    match colors.get(next) {
+                        Some(other) if *other == color => return None,
+                        Some(_) => {}
+                        None => {
+                            colors.insert(next, !color);
+                            queue.push_back(next);
+                        }
+                    }
+                }
+            }
+        }
+        Some(
+            colors
+                .into_iter()
+                .map(|(key, color)| (key.clone(), color))
+                .collect(),
+        )
+    }
+
+    /// Returns a graph with the same entries and an edge from `a` to `b` whenever `b` is reachable from `a`.
+    /// A node on a cycle gets an edge to itself. Edge weights are dropped.
+    ///
+    /// It runs a breadth-first search from every node, which costs O(V * (V + E)),
+    /// and the result can hold up to V * V edges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3)]);
+    /// let closure = graph.transitive_closure();
+    /// assert_eq!(closure.edge_count(), 3);
+    /// assert_eq!(closure.shortest_path(&1, &3), Some(vec![1, 3]));
+    /// ```
+    pub fn transitive_closure(&self) -> IndexedGraph<K, V>
+    where
+        V: Clone,
+    {
+        let mut closure = IndexedGraph::new();
+        for (key, value) in self.keys.iter().zip(&self.values) {
+            closure.insert(key.clone(), value.clone());
+        }
+        for from in self.i.keys() {
+            let mut reached = BTreeSet::new();
+            let mut queue = VecDeque::new();
+            queue.push_back(from);
+            while let Some(node) = queue.pop_front() {
+                for next in self.successors(node) {
+                    if reached.insert(next) {
+                        queue.push_back(next);
+                    }
+                }
+            }
+            for to in reached {
+                if self.i.contains_key(to) {
+                    closure.insert_edge(from.clone(), to.clone());
+                }
+            }
+        }
+        closure
+    }
+
+    /// Returns the eccentricity of the node, the greatest distance from it to any node it is connected to.
+    /// Edges are treated as undirected and distances count edges, so only the node's own component is considered.
+    /// Returns `None` if the key is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3)]);
+    /// assert_eq!(graph.eccentricity(&1), Some(2));
+    /// assert_eq!(graph.eccentricity(&2), Some(1));
+    /// assert_eq!(graph.eccentricity(&4), None);
+    /// ```
+    pub fn eccentricity(&self, key: &K) -> Option<usize> {
+        let key = self.get_key(key)?;
+        let distances = Self::distances(&self.undirected_adjacency(), key);
+        distances.values().max().copied()
+    }
+
+    /// Returns the center of the graph, the nodes with the smallest eccentricity, in sorted order.
+    /// See `eccentricity` for how disconnected graphs are handled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4), (4, 5)]);
+    /// assert_eq!(graph.center(), [3]);
+    /// ```
+    pub fn center(&self) -> Vec<K> {
+        let adjacency = self.undirected_adjacency();
+        let eccentricities: Vec<(&K, usize)> = adjacency
+            .keys()
+            .map(|key| {
+                (
+                    *key,
+                    Self::distances(&adjacency, key)
+                        .values()
+                        .max()
+                        .copied()
+                        .unwrap_or(0),
+                )
+            })
+            .collect();
+        let smallest = eccentricities.iter().map(|(_, e)| *e).min();
+        eccentricities
+            .into_iter()
+            .filter(|(_, e)| Some(*e) == smallest)
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Returns the closeness centrality of the node, the reciprocal of the summed distances
+    /// from it to every node it can reach by following edges.
+    /// Returns `0.0` if the node reaches no other node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 0..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// // a star with 0 in the middle, and a leaf pointing back to it
+    /// graph.extend_edges(vec![(0, 1), (0, 2), (0, 3), (1, 0)]);
+    /// assert_eq!(graph.closeness_centrality(&0), 1.0 / 3.0);
+    /// assert_eq!(graph.closeness_centrality(&1), 1.0 / 5.0);
+    /// assert_eq!(graph.closeness_centrality(&2), 0.0);
+    /// ```
+    pub fn closeness_centrality(&self, key: &K) -> f64 {
+        let key = match self.get_key(key) {
+            Some(key) => key,
+            None => return 0.0,
+        };
+        let total: usize = Self::distances(&self.directed_adjacency(), key)
+            .values()
+            .sum();
+        if total == 0 {
+            0.0
+        } else {
+            1.0 / total as f64
+        }
+    }
+
+    /// Returns the keys with exactly `degree` outgoing edges, in sorted order.
+    /// Parallel edges are counted one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (1, 4), (2, 3)]);
+    /// assert_eq!(graph.keys_with_out_degree(0), [&3, &4]);
+    /// assert_eq!(graph.keys_with_out_degree(3), [&1]);
+    /// ```
+    pub fn keys_with_out_degree(&self, degree: usize) -> Vec<&K> {
+        self.i
+            .keys()
+            .filter(|key| self.edges.get(key).map_or(0, |targets| targets.len()) == degree)
+            .collect()
+    }
+
+    /// Returns the density of the graph, the number of edges divided by the `n * (n - 1)`
+    /// possible directed edges between `n` nodes.
+    /// Returns `0.0` for graphs with fewer than two nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// assert_eq!(graph.density(), 0.0);
+    /// graph.extend_edges(vec![(1, 2), (2, 1), (1, 3), (3, 1), (2, 3), (3, 2)]);
+    /// assert!((graph.density() - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn density(&self) -> f64 {
+        let nodes = self.node_count();
+        if nodes < 2 {
+            return 0.0;
+        }
+        self.edge_count() as f64 / (nodes * (nodes - 1)) as f64
+    }
+
+    /// Returns the keys reachable from `start` in breadth-first order, beginning with `start`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 3), (1, 2), (2, 4), (3, 4)]);
+    /// assert_eq!(graph.bfs(&1), [&1, &3, &2, &4]);
+    /// ```
+    pub fn bfs<'a>(&'a self, start: &'a K) -> Vec<&'a K> {
+        let mut order = vec![start];
+        let mut seen = BTreeSet::new();
+        seen.insert(start);
+        let mut next = 0;
+        while next < order.len() {
+            for to in self.successors(order[next]) {
+                if seen.insert(to) {
+                    order.push(to);
+                }
+            }
+            next += 1;
+        }
+        order
+    }
+
+    /// Returns the breadth-first spanning tree from `root`: a new graph holding the keys
+    /// reachable from `root` with all of their values, and for every key but `root`
+    /// one edge from the key it was first discovered from. Edge weights are dropped.
+    /// Returns an empty graph if `root` is not in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// // 3 -> 2 is a cross edge, 5 is unreachable
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (3, 2), (2, 4), (5, 1)]);
+    /// let tree = graph.bfs_tree(&1);
+    /// assert_eq!(tree.edge_list(), vec![(1, 2), (1, 3), (2, 4)]);
+    /// assert_eq!(tree.node_count(), 4);
+    /// assert!(!tree.contains_key(&5));
+    /// ```
+    pub fn bfs_tree(&self, root: &K) -> IndexedGraph<K, V>
+    where
+        V: Clone,
+    {
+        let mut tree = IndexedGraph::new();
+        if !self.contains_key(root) {
+            return tree;
+        }
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        let mut edges = vec![];
+        seen.insert(root);
+        queue.push_back(root);
+        while let Some(node) = queue.pop_front() {
+            for next in self.successors(node) {
+                if self.i.contains_key(next) && seen.insert(next) {
+                    edges.push((node.clone(), next.clone()));
+                    queue.push_back(next);
+                }
+            }
+        }
+        for (key, value) in self.iter() {
+            if seen.contains(key) {
+                tree.insert(key.clone(), value.clone());
+            }
+        }
+        tree.extend_edges(edges);
+        tree
+    }
+
+    /// Turns the graph into a `FrozenGraph`, which can't be changed anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    ///
+    /// let frozen = graph.freeze();
+    /// assert_eq!(frozen.get(&2), vec![&"b"]);
+    /// assert_eq!(frozen.bfs(&1), [&1, &2]);
+    /// ```
+    pub fn freeze(self) -> FrozenGraph<K, V, E> {
+        FrozenGraph {
+            keys: self.keys.into_boxed_slice(),
+            values: self.values.into_boxed_slice(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|(from, targets)| (from, targets.into_boxed_slice()))
+                .collect(),
+            i: self
+                .i
+                .into_iter()
+                .map(|(key, indexes)| (key, indexes.into_boxed_slice()))
+                .collect(),
+            max_nodes: self.max_nodes,
+        }
+    }
+
+    /// Returns every triangle of the graph, three nodes which are all connected to each other,
+    /// treating edges as undirected.
+    /// Each triangle is reported once with its keys in sorted order, and the triangles are sorted.
+    ///
+    /// Every edge is only followed from its end with the lower degree,
+    /// which keeps the enumeration fast around high degree nodes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)]);
+    /// assert_eq!(
+    ///     graph.triangles(),
+    ///     [(1, 2, 3), (1, 2, 4), (1, 3, 4), (2, 3, 4)]
+    /// );
+    /// ```
+    pub fn triangles(&self) -> Vec<(K, K, K)> {
+        let adjacency = self.undirected_adjacency();
+        let ranks_higher = |a: &K, b: &K| (adjacency[a].len(), a) > (adjacency[b].len(), b);
+        let mut triangles = BTreeSet::new();
+        for (node, neighbors) in &adjacency {
+            let higher: Vec<&K> = neighbors
+                .iter()
+                .copied()
+                .filter(|other| ranks_higher(other, node))
+                .collect();
+            for (idx, first) in higher.iter().enumerate() {
+                for second in &higher[idx + 1..] {
+                    if adjacency[first].contains(second) {
+                        let mut keys = [*node, *first, *second];
+                        keys.sort();
+                        triangles.insert((keys[0].clone(), keys[1].clone(), keys[2].clone()));
+                    }
+                }
+            }
+        }
+        triangles.into_iter().collect()
+    }
+
+    /// Returns the local clustering coefficient of the node, treating edges as undirected:
+    /// the fraction of pairs of its neighbors which are connected themselves.
+    /// Nodes with fewer than two neighbors, and keys not in the graph, get `0.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (3, 4)]);
+    /// assert_eq!(graph.clustering_coefficient(&1), 1.0);
+    /// assert_eq!(graph.clustering_coefficient(&3), 1.0 / 3.0);
+    /// assert_eq!(graph.clustering_coefficient(&4), 0.0);
+    /// ```
+    pub fn clustering_coefficient(&self, key: &K) -> f64 {
+        let adjacency = self.undirected_adjacency();
+        let neighbors: Vec<&K> = match adjacency.get(key) {
+            Some(neighbors) if neighbors.len() >= 2 => neighbors.iter().copied().collect(),
+            _ => return 0.0,
+        };
+        let mut links = 0;
+        for (idx, first) in neighbors.iter().enumerate() {
+            for second in &neighbors[idx + 1..] {
+                if adjacency[first].contains(second) {
+                    links += 1;
+                }
+            }
+        }
+        let pairs = neighbors.len() * (neighbors.len() - 1) / 2;
+        links as f64 / pairs as f64
+    }
+
+    /// Returns the global clustering coefficient of the graph, treating edges as undirected:
+    /// three times the number of triangles divided by the number of connected triples of nodes.
+    /// Returns `0.0` if there are no connected triples.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3)]);
+    /// assert_eq!(graph.global_clustering_coefficient(), 0.0);
+    /// graph.insert_edge(3, 1);
+    /// assert_eq!(graph.global_clustering_coefficient(), 1.0);
+    /// ```
+    pub fn global_clustering_coefficient(&self) -> f64 {
+        let triples: usize = self
+            .undirected_adjacency()
+            .values()
+            .map(|neighbors| neighbors.len() * neighbors.len().saturating_sub(1) / 2)
+            .sum();
+        if triples == 0 {
+            return 0.0;
+        }
+        (3 * self.triangles().len()) as f64 / triples as f64
+    }
+
+    /// Checks that the path can be walked in the graph: every key is present
+    /// and there is an edge from each key to the next one.
+    /// Returns `Err(i)` for the first position `i` in the path which is missing,
+    /// or which can't be reached from the key before it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4)]);
+    /// assert_eq!(graph.validate_path(&[1, 2, 3, 4]), Ok(()));
+    /// assert_eq!(graph.validate_path(&[1, 3, 4]), Err(1));
+    /// assert_eq!(graph.validate_path(&[3, 4, 5]), Err(2));
+    /// ```
+    pub fn validate_path(&self, path: &[K]) -> Result<(), usize> {
+        for (idx, key) in path.iter().enumerate() {
+            if !self.contains_key(key) {
+                return Err(idx);
+            }
+            if idx > 0 && !self.successors(&path[idx - 1]).any(|to| to == key) {
+                return Err(idx);
+            }
+        }
+        Ok(())
+    }
+
+    /// Finds the cheapest path from `from` to `to` by the A* algorithm, summing the edge weights.
+    /// Returns the total cost together with the path, including both ends.
+    ///
+    /// The `heuristic` estimates the remaining cost from a key to `to`.
+    /// It must never overestimate for the result to be the cheapest path.
+    /// A heuristic which always returns zero makes this Dijkstra's algorithm.
+    /// Weights must not be negative, and `E::default()` is taken as zero cost.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_weighted_edge(1, 2, 1);
+    /// graph.insert_weighted_edge(2, 4, 5);
+    /// graph.insert_weighted_edge(1, 3, 2);
+    /// graph.insert_weighted_edge(3, 4, 1);
+    ///
+    /// assert_eq!(graph.astar(&1, &4, |_| 0), Some((3, vec![1, 3, 4])));
+    /// // the number of hops left is a lower bound here, since every weight is at least 1
+    /// let hops = |key: &i32| if *key == 4 { 0 } else { 1 };
+    /// assert_eq!(graph.astar(&1, &4, hops), Some((3, vec![1, 3, 4])));
+    /// assert_eq!(graph.astar(&4, &1, |_| 0), None);
+    /// ```
+    pub fn astar<F: Fn(&K) -> E>(&self, from: &K, to: &K, heuristic: F) -> Option<(E, Vec<K>)>
+    where
+        E: Copy + Ord + Add<Output = E> + Default,
+    {
+        let mut best = BTreeMap::new();
+        let mut parents: BTreeMap<&K, &K> = BTreeMap::new();
+        let mut heap = BinaryHeap::new();
+        best.insert(from, E::default());
+        heap.push(Reverse((heuristic(from), E::default(), from)));
+        while let Some(Reverse((_, cost, node))) = heap.pop() {
+            if node == to {
+                let mut path = vec![node.clone()];
+                let mut current = node;
+                while let Some(parent) = parents.get(current) {
+                    path.push((*parent).clone());
+                    current = parent;
+                }
+                path.reverse();
+                return Some((cost, path));
+            }
+            if cost > best[node] {
+                continue;
+            }
+            for (next, weight) in self.edges.get(node).into_iter().flatten() {
+                let next_cost = cost + *weight;
+                if best.get(next).map_or(true, |known| next_cost < *known) {
+                    best.insert(next, next_cost);
+                    parents.insert(next, node);
+                    heap.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the histograms of the in-degrees and the out-degrees of the nodes,
+    /// each mapping a degree to the number of nodes having it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (1, 4), (2, 3)]);
+    /// let (in_degrees, out_degrees) = graph.degree_distribution();
+    /// assert_eq!(in_degrees.into_iter().collect::<Vec<_>>(), [(0, 1), (1, 2), (2, 1)]);
+    /// assert_eq!(out_degrees.into_iter().collect::<Vec<_>>(), [(0, 2), (1, 1), (3, 1)]);
+    /// ```
+    pub fn degree_distribution(&self) -> (BTreeMap<usize, usize>, BTreeMap<usize, usize>) {
+        let mut in_degrees: BTreeMap<&K, usize> = self.i.keys().map(|key| (key, 0)).collect();
+        for (_, to, _) in self.edge_triples() {
+            if let Some(degree) = in_degrees.get_mut(to) {
+                *degree += 1;
+            }
+        }
+        let mut in_histogram = BTreeMap::new();
+        let mut out_histogram = BTreeMap::new();
+        for (key, degree) in in_degrees {
+            *in_histogram.entry(degree).or_insert(0) += 1;
+            *out_histogram.entry(self.out_degree(key)).or_insert(0) += 1;
+        }
+        (in_histogram, out_histogram)
+    }
+
+    /// Returns a graph with the same entries and an edge from `a` to `b` for every two distinct nodes
+    /// without an edge from `a` to `b` in this graph. Edge weights are dropped.
+    ///
+    /// The result has up to V * (V - 1) edges, and building it takes O(V * V) time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    ///
+    /// let complement = graph.complement();
+    /// assert_eq!(complement.get(&1), vec![&"a"]);
+    /// assert_eq!(complement.edge_count(), 1);
+    /// assert_eq!(complement.parallel_edge_count(&2, &1), 1);
+    /// ```
+    pub fn complement(&self) -> IndexedGraph<K, V>
+    where
+        V: Clone,
+    {
+        let adjacency = self.directed_adjacency();
+        let mut complement = IndexedGraph::new();
+        for (key, value) in self.keys.iter().zip(&self.values) {
+            complement.insert(key.clone(), value.clone());
+        }
+        for (from, targets) in &adjacency {
+            for to in adjacency.keys() {
+                if from != to && !targets.contains(to) {
+                    complement.insert_edge((*from).clone(), (*to).clone());
+                }
+            }
+        }
+        complement
+    }
+
+    /// Returns the lowest common ancestor of `a` and `b` in a directed acyclic graph:
+    /// the deepest key which reaches both of them, where a key counts as its own ancestor.
+    /// The depth of a key is the length of the longest path to it from a key without incoming edges.
+    /// When several common ancestors are equally deep, the smallest key is returned.
+    ///
+    /// Returns `None` if the keys share no ancestor, if either is not in the graph,
+    /// or if the graph has a cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// // a diamond from 1 down to 4, and 5 on its own
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (2, 4), (3, 4)]);
+    /// assert_eq!(graph.lowest_common_ancestor(&2, &3), Some(1));
+    /// assert_eq!(graph.lowest_common_ancestor(&2, &4), Some(2));
+    /// assert_eq!(graph.lowest_common_ancestor(&4, &5), None);
+    /// ```
+    pub fn lowest_common_ancestor(&self, a: &K, b: &K) -> Option<K> {
+        if !self.contains_key(a) || !self.contains_key(b) {
+            return None;
+        }
+        let depths = self.topological_depths()?;
+        let mut of_a = self.ancestors(a);
+        of_a.insert(a.clone());
+        let mut of_b = self.ancestors(b);
+        of_b.insert(b.clone());
+        let mut lowest: Option<(&K, usize)> = None;
+        for key in of_a.intersection(&of_b) {
+            if let Some(depth) = depths.get(key) {
+                if lowest.map_or(true, |(_, deepest)| *depth > deepest) {
+                    lowest = Some((key, *depth));
+                }
+            }
+        }
+        lowest.map(|(key, _)| key.clone())
+    }
+
+    /// Returns the number of weakly connected components, treating edges as undirected.
+    /// Uses union-find, so unlike `connected_components` no component lists are built.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (5, 4)]);
+    /// assert_eq!(graph.component_count(), 2);
+    /// ```
+    pub fn component_count(&self) -> usize {
+        let position: BTreeMap<&K, usize> = self
+            .i
+            .keys()
+            .enumerate()
+            .map(|(idx, key)| (key, idx))
+            .collect();
+        let mut parents: Vec<usize> = (0..position.len()).collect();
+        fn root(parents: &mut [usize], mut node: usize) -> usize {
+            while parents[node] != node {
+                parents[node] = parents[parents[node]];
+                node = parents[node];
+            }
+            node
+        }
+        let mut count = position.len();
+        for (from, to, _) in self.edge_triples() {
+            if let (Some(from), Some(to)) = (position.get(from), position.get(to)) {
+                let (from, to) = (root(&mut parents, *from), root(&mut parents, *to));
+                if from != to {
+                    parents[from] = to;
+                    count -= 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Returns true if the graph, treated as undirected, forms a single connected component.
+    /// An empty graph has no components and is considered connected.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.insert_edge(1, 2);
+    /// assert!(!graph.is_connected());
+    /// graph.insert_edge(3, 2);
+    /// assert!(graph.is_connected());
+    /// ```
+    pub fn is_connected(&self) -> bool {
+        self.component_count() <= 1
+    }
+
+    /// Returns the number of distinct edges whose ends are assigned to different parts by
+    /// `partition`, which maps keys to a part label. In a well-partitioned graph this is zero.
+    /// Parallel edges count once, edges with an end missing from the graph or from `partition`
+    /// are ignored.
+    ///
+    /// Weakly connected components can't serve as the partition, since the ends of every edge
+    /// always share one. Colorings such as `two_coloring` or `greedy_coloring`, or any clustering
+    /// built by the caller, can.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeMap;
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 1), (3, 4)]);
+    /// let clusters = BTreeMap::from([(1, "left"), (2, "left"), (3, "right"), (4, "right")]);
+    /// assert_eq!(graph.inter_partition_edges(&clusters), 0);
+    /// graph.insert_edge(2, 3);
+    /// graph.insert_edge(2, 3);
+    /// assert_eq!(graph.inter_partition_edges(&clusters), 1);
+    /// ```
+    pub fn inter_partition_edges<P: PartialEq>(&self, partition: &BTreeMap<K, P>) -> usize {
+        self.directed_adjacency()
+            .into_iter()
+            .filter_map(|(from, targets)| Some((partition.get(from)?, targets)))
+            .map(|(part, targets)| {
+                targets
+                    .into_iter()
+                    .filter(|to| partition.get(*to).is_some_and(|other| other != part))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Returns the number of edges on the longest path from a key without incoming edges to
+    /// any other key, or `None` if the graph has a cycle. An empty graph has depth 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4), (1, 4)]);
+    /// assert_eq!(graph.depth(), Some(3));
+    /// graph.insert_edge(4, 1);
+    /// assert_eq!(graph.depth(), None);
+    /// ```
+    pub fn depth(&self) -> Option<usize> {
+        let depths = self.topological_depths()?;
+        Some(depths.values().copied().max().unwrap_or(0))
+    }
+
+    /// Returns every key reachable from `start` by following at most `hops` edges.
+    /// The start itself is only included if a cycle leads back to it within `hops` edges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4)]);
+    /// assert_eq!(graph.neighbors_within(&1, 1).into_iter().collect::<Vec<_>>(), [2]);
+    /// assert_eq!(graph.neighbors_within(&1, 2).into_iter().collect::<Vec<_>>(), [2, 3]);
+    /// assert!(!graph.neighbors_within(&1, 5).contains(&1));
+    /// graph.insert_edge(3, 1);
+    /// assert!(graph.neighbors_within(&1, 3).contains(&1));
+    /// ```
+    pub fn neighbors_within(&self, start: &K, hops: usize) -> BTreeSet<K> {
+        let mut found = BTreeSet::new();
+        if !self.contains_key(start) {
+            return found;
+        }
+        let mut expanded = BTreeSet::from([start]);
+        let mut frontier = vec![start];
+        for _ in 0..hops {
+            let mut next_frontier = vec![];
+            for node in frontier {
+                for next in self.successors(node) {
+                    if !self.contains_key(next) {
+                        continue;
+                    }
+                    found.insert(next.clone());
+                    if expanded.insert(next) {
+                        next_frontier.push(next);
+                    }
+                }
+            }
+            if next_frontier.is_empty() {
+                break;
+            }
+            frontier = next_frontier;
+        }
+        found
+    }
+
+    /// Computes the PageRank score of every key by power iteration, starting from a uniform
+    /// distribution. `damping` is the probability of following an edge, commonly `0.85`.
+    /// Parallel edges count once, and keys without outgoing edges spread their rank over all
+    /// keys, so the scores sum to about `1.0`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 3), (2, 3), (4, 3), (3, 1)]);
+    /// let ranks = graph.pagerank(0.85, 50);
+    /// let best = ranks.iter().max_by(|a, b| a.1.total_cmp(b.1)).unwrap();
+    /// assert_eq!(*best.0, 3);
+    /// assert!((ranks.values().sum::<f64>() - 1.0).abs() < 1e-9);
+    /// ```
+    pub fn pagerank(&self, damping: f64, iterations: usize) -> BTreeMap<K, f64> {
+        let adjacency = self.directed_adjacency();
+        let count = adjacency.len() as f64;
+        let mut ranks: BTreeMap<&K, f64> =
+            adjacency.keys().map(|key| (*key, 1.0 / count)).collect();
+        for _ in 0..iterations {
+            let dangling: f64 = adjacency
+                .iter()
+                .filter(|(_, targets)| targets.is_empty())
+                .map(|(key, _)| ranks[key])
+                .sum();
+            let base = (1.0 - damping) / count + damping * dangling / count;
+            let mut next: BTreeMap<&K, f64> = adjacency.keys().map(|key| (*key, base)).collect();
+            for (from, targets) in &adjacency {
+                let share = damping * ranks[from] / targets.len() as f64;
+                for to in targets {
+                    *next.get_mut(to).unwrap() += share;
+                }
+            }
+            ranks = next;
+        }
+        ranks
+            .into_iter()
+            .map(|(key, rank)| (key.clone(), rank))
+            .collect()
+    }
+
+    /// Walks up to `steps` edges from `start`, picking uniformly among the outgoing edges at each
+    /// step. Stops early at a key without outgoing edges. Returns the visited keys including
+    /// `start`, or an empty `Vec` if `start` is not in the graph.
+    ///
+    /// Requires the `rand` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    /// use rand::{rngs::StdRng, SeedableRng};
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (2, 1), (3, 1), (3, 4)]);
+    /// let walk = graph.random_walk(&1, 10, &mut StdRng::seed_from_u64(7));
+    /// assert_eq!(walk, graph.random_walk(&1, 10, &mut StdRng::seed_from_u64(7)));
+    /// assert_eq!(walk[0], 1);
+    /// assert!(walk.len() <= 11);
+    /// assert!(graph.validate_path(&walk).is_ok());
+    ///
+    /// graph.insert(5, ());
+    /// assert_eq!(graph.random_walk(&5, 10, &mut StdRng::seed_from_u64(7)), [5]);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn random_walk(&self, start: &K, steps: usize, rng: &mut impl rand::Rng) -> Vec<K> {
+        let mut node = match self.get_key(start) {
+            Some(key) => key,
+            None => return vec![],
+        };
+        let mut walk = vec![node.clone()];
+        for _ in 0..steps {
+            let targets: Vec<&K> = self
+                .successors(node)
+                .filter(|to| self.contains_key(to))
+                .collect();
+            if targets.is_empty() {
+                break;
+            }
+            node = targets[rng.gen_range(0..targets.len())];
+            walk.push(node.clone());
+        }
+        walk
+    }
+
+    /// Returns `true` if a directed path visits every key exactly once. An empty graph has
+    /// the empty path.
+    ///
+    /// Uses backtracking, which takes exponential time in the worst case, so it is only
+    /// meant for small graphs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(3, 4), (1, 2), (2, 3)]);
+    /// assert!(graph.has_hamiltonian_path());
+    /// graph.insert(5, ());
+    /// assert!(!graph.has_hamiltonian_path());
+    /// ```
+    pub fn has_hamiltonian_path(&self) -> bool {
+        let adjacency = self.directed_adjacency();
+        let position: BTreeMap<&K, usize> = adjacency
+            .keys()
+            .enumerate()
+            .map(|(idx, key)| (*key, idx))
+            .collect();
+        let successors: Vec<Vec<usize>> = adjacency
+            .values()
+            .map(|targets| targets.iter().map(|to| position[to]).collect())
+            .collect();
+        fn extend(
+            successors: &[Vec<usize>],
+            visited: &mut [bool],
+            node: usize,
+            len: usize,
+        ) -> bool {
+            if len == visited.len() {
+                return true;
+            }
+            for &next in &successors[node] {
+                if !visited[next] {
+                    visited[next] = true;
+                    if extend(successors, visited, next, len + 1) {
+                        return true;
+                    }
+                    visited[next] = false;
+                }
+            }
+            false
+        }
+        let count = successors.len();
+        if count == 0 {
+            return true;
+        }
+        let mut visited = vec![false; count];
+        (0..count).any(|start| {
+            visited[start] = true;
+            let found = extend(&successors, &mut visited, start, 1);
+            visited[start] = false;
+            found
+        })
+    }
+
+    /// Finds a trail which follows every edge exactly once, using Hierholzer's algorithm.
+    /// The trail is a closed circuit when every key has as many incoming as outgoing edges,
+    /// otherwise it has to start at the one key with an extra outgoing edge.
+    /// Returns `None` if no such trail exists, and an empty trail for a graph without edges.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (1, 4)]);
+    /// let trail = graph.eulerian_path().unwrap();
+    /// assert_eq!(trail.len(), graph.edge_count() + 1);
+    /// assert_eq!((trail[0], trail[4]), (1, 4));
+    ///
+    /// graph.insert_edge(1, 4);
+    /// assert_eq!(graph.eulerian_path(), None);
+    /// ```
+    pub fn eulerian_path(&self) -> Option<Vec<K>> {
+        let position: BTreeMap<&K, usize> = self
+            .i
+            .keys()
+            .enumerate()
+            .map(|(idx, key)| (key, idx))
+            .collect();
+        let mut successors: Vec<Vec<usize>> = vec![vec![]; position.len()];
+        let mut balance: Vec<isize> = vec![0; position.len()];
+        let mut edge_count = 0;
+        for (from, to, _) in self.edge_triples() {
+            if let (Some(&from), Some(&to)) = (position.get(from), position.get(to)) {
+                successors[from].push(to);
+                balance[from] += 1;
+                balance[to] -= 1;
+                edge_count += 1;
+            }
+        }
+        if edge_count == 0 {
+            return Some(vec![]);
+        }
+        let mut start = None;
+        for (node, surplus) in balance.iter().enumerate() {
+            match surplus {
+                0 => {}
+                1 if start.is_none() => start = Some(node),
+                1 | -1 => {}
+                _ => return None,
+            }
+        }
+        if balance.iter().filter(|surplus| **surplus != 0).count() > 2 {
+            return None;
+        }
+        let start = start.unwrap_or_else(|| {
+            successors
+                .iter()
+                .position(|targets| !targets.is_empty())
+                .unwrap()
+        });
+        let mut next_edge = vec![0; successors.len()];
+        let mut stack = vec![start];
+        let mut trail = vec![];
+        while let Some(&node) = stack.last() {
+            if next_edge[node] < successors[node].len() {
+                stack.push(successors[node][next_edge[node]]);
+                next_edge[node] += 1;
+            } else {
+                trail.push(node);
+                stack.pop();
+            }
+        }
+        if trail.len() != edge_count + 1 {
+            return None;
+        }
+        let keys: Vec<&K> = position.keys().copied().collect();
+        Some(
+            trail
+                .into_iter()
+                .rev()
+                .map(|node| keys[node].clone())
+                .collect(),
+        )
+    }
+
+    /// Returns the keys at a directed distance of exactly two from `key`: the successors of its
+    /// successors, without `key` itself and without its direct successors, even when those are
+    /// also two edges away along another route.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=5 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (2, 3), (2, 4), (3, 5), (4, 1)]);
+    /// let two_hops: Vec<_> = graph.two_hop_neighbors(&1).into_iter().collect();
+    /// assert_eq!(two_hops, [4, 5]);
+    /// ```
+    pub fn two_hop_neighbors(&self, key: &K) -> BTreeSet<K> {
+        let adjacency = self.directed_adjacency();
+        let direct = match adjacency.get(key) {
+            Some(direct) => direct,
+            None => return BTreeSet::new(),
+        };
+        direct
+            .iter()
+            .flat_map(|next| &adjacency[next])
+            .filter(|far| **far != key && !direct.contains(*far))
+            .map(|far| (*far).clone())
+            .collect()
+    }
+
+    /// Returns the diameter of the graph, the greatest distance between two connected nodes.
+    /// Edges are treated as undirected, like in `eccentricity`, and a breadth-first search is run
+    /// from every node. A disconnected graph gives the greatest diameter of its components.
+    /// Returns `None` if the graph is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=7 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (3, 2), (3, 4), (4, 5), (6, 7)]);
+    /// assert_eq!(graph.diameter(), Some(4));
+    /// assert_eq!(IndexedGraph::<u8, ()>::new().diameter(), None);
+    /// ```
+    pub fn diameter(&self) -> Option<usize> {
+        let adjacency = self.undirected_adjacency();
+        adjacency
+            .keys()
+            .map(|key| {
+                Self::distances(&adjacency, key)
+                    .values()
+                    .max()
+                    .copied()
+                    .unwrap_or(0)
+            })
+            .max()
+    }
+
+    /// Makes a copy of the graph with the keys replaced by `0..n`, assigned in sorted key order,
+    /// together with the original keys so that `keys[id]` is the key which became `id`.
+    /// Values keep their insertion order and edges keep their weights.
+    /// Edges pointing to keys which are not in the graph are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert("c", 3);
+    /// graph.insert("a", 1);
+    /// graph.insert("b", 2);
+    /// graph.extend_edges(vec![("a", "c"), ("c", "b")]);
+    /// let (relabeled, keys) = graph.relabel_to_indices();
+    /// assert_eq!(keys, ["a", "b", "c"]);
+    /// assert_eq!(relabeled.edge_list(), vec![(0, 2), (2, 1)]);
+    /// assert_eq!(relabeled.get(&2), vec![&3]);
+    /// ```
+    pub fn relabel_to_indices(&self) -> (IndexedGraph<usize, V, E>, Vec<K>)
+    where
+        V: Clone,
+        E: Clone,
+    {
+        let ids: BTreeMap<&K, usize> = self
+            .i
+            .keys()
+            .enumerate()
+            .map(|(id, key)| (key, id))
+            .collect();
+        let mut graph = IndexedGraph::new_weighted();
+        for (key, value) in self.keys.iter().zip(&self.values) {
+            graph.insert(ids[key], value.clone());
+        }
+        for (from, to, weight) in self.edge_triples() {
+            if let (Some(from), Some(to)) = (ids.get(from), ids.get(to)) {
+                graph.insert_weighted_edge(*from, *to, weight.clone());
+            }
+        }
+        (graph, self.i.keys().cloned().collect())
+    }
+
+    /// Returns the keys, in sorted order, from which `target` can't be reached along directed
+    /// edges. This is the complement of `ancestors`, the target itself is never included.
+    /// If the target is not in the graph, every key is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 4)]);
+    /// assert_eq!(graph.unreachable_to(&3), [4]);
+    /// assert_eq!(graph.unreachable_to(&1), [2, 3, 4]);
+    /// ```
+    pub fn unreachable_to(&self, target: &K) -> Vec<K> {
+        let ancestors = self.ancestors(target);
+        self.i
+            .keys()
+            .filter(|key| *key != target && !ancestors.contains(*key))
+            .cloned()
+            .collect()
+    }
+
+    /// Colors the nodes so that no two nodes joined by an edge share a color, with edges treated
+    /// as undirected. Nodes are colored greedily from the highest degree down, ties in key order,
+    /// each with the smallest color number its neighbors don't use yet.
+    /// The number of colors used is an upper bound on the chromatic number, not always the best.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BTreeSet;
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (3, 4)]);
+    /// let colors = graph.greedy_coloring();
+    /// let triangle: BTreeSet<_> = [1, 2, 3].iter().map(|key| colors[key]).collect();
+    /// assert_eq!(triangle.len(), 3);
+    /// assert_ne!(colors[&3], colors[&4]);
+    /// ```
+    pub fn greedy_coloring(&self) -> BTreeMap<K, usize> {
+        let adjacency = self.undirected_adjacency();
+        let mut order: Vec<&K> = adjacency.keys().copied().collect();
+        order.sort_by_key(|key| Reverse(adjacency[key].len()));
+        let mut colors: BTreeMap<&K, usize> = BTreeMap::new();
+        for key in order {
+            let used: BTreeSet<usize> = adjacency[key]
+                .iter()
+                .filter_map(|neighbor| colors.get(neighbor).copied())
+                .collect();
+            let color = (0..).find(|color| !used.contains(color)).unwrap();
+            colors.insert(key, color);
+        }
+        colors
+            .into_iter()
+            .map(|(key, color)| (key.clone(), color))
+            .collect()
+    }
+
+    /// Returns every maximal clique, a set of nodes which are all joined to each other and can't
+    /// be extended by another node, with edges treated as undirected. An isolated node is a
+    /// clique of its own. Each clique is sorted, and so is the list of cliques.
+    ///
+    /// Uses the Bron–Kerbosch algorithm with pivoting. A graph can have exponentially many
+    /// maximal cliques, so this can take exponential time in the worst case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1), (3, 4)]);
+    /// assert_eq!(graph.maximal_cliques(), vec![vec![1, 2, 3], vec![3, 4]]);
+    ///
+    /// let empty: IndexedGraph<i32, ()> = IndexedGraph::new();
+    /// assert!(empty.maximal_cliques().is_empty());
+    /// ```
+    pub fn maximal_cliques(&self) -> Vec<Vec<K>> {
+        fn extend<'a, K: Ord + Clone>(
+            adjacency: &BTreeMap<&'a K, BTreeSet<&'a K>>,
+            clique: &mut Vec<&'a K>,
+            mut candidates: BTreeSet<&'a K>,
+            mut excluded: BTreeSet<&'a K>,
+            cliques: &mut Vec<Vec<K>>,
+        ) {
+            if candidates.is_empty() && excluded.is_empty() {
+                let mut found: Vec<K> = clique.iter().map(|key| (*key).clone()).collect();
+                found.sort();
+                cliques.push(found);
+                return;
+            }
+            let pivot = candidates
+                .union(&excluded)
+                .max_by_key(|key| adjacency[*key].intersection(&candidates).count())
+                .copied()
+                .unwrap();
+            let branches: Vec<&K> = candidates.difference(&adjacency[pivot]).copied().collect();
+            for key in branches {
+                let neighbors = &adjacency[key];
+                clique.push(key);
+                extend(
+                    adjacency,
+                    clique,
+                    candidates.intersection(neighbors).copied().collect(),
+                    excluded.intersection(neighbors).copied().collect(),
+                    cliques,
+                );
+                clique.pop();
+                candidates.remove(key);
+                excluded.insert(key);
+            }
+        }
+
+        let adjacency = self.undirected_adjacency();
+        let mut cliques = vec![];
+        if adjacency.is_empty() {
+            return cliques;
+        }
+        let candidates = adjacency.keys().copied().collect();
+        extend(
+            &adjacency,
+            &mut vec![],
+            candidates,
+            BTreeSet::new(),
+            &mut cliques,
+        );
+        cliques.sort();
+        cliques
+    }
+
+    /// Returns the line graph, which has a node for every edge and joins two of them when their
+    /// edges share an end. Parallel edges make a single node, and each pair of nodes gets one
+    /// edge, from the smaller to the greater. Edges pointing to keys which are not in the graph
+    /// are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3)]);
+    /// let line = graph.line_graph();
+    /// assert_eq!(line.node_count(), 2);
+    /// assert_eq!(line.edge_list(), vec![((1, 2), (2, 3))]);
+    /// ```
+    pub fn line_graph(&self) -> IndexedGraph<(K, K), ()> {
+        let adjacency = self.directed_adjacency();
+        let mut graph = IndexedGraph::new();
+        let mut incident: BTreeMap<&K, BTreeSet<(&K, &K)>> = BTreeMap::new();
+        for (from, targets) in &adjacency {
+            for to in targets {
+                graph.insert(((*from).clone(), (*to).clone()), ());
+                incident.entry(*from).or_default().insert((*from, *to));
+                incident.entry(*to).or_default().insert((*from, *to));
+            }
+        }
+        let mut joined = BTreeSet::new();
+        for edges in incident.values() {
+            for (idx, first) in edges.iter().enumerate() {
+                for second in edges.iter().skip(idx + 1) {
+                    joined.insert((*first, *second));
+                }
+            }
+        }
+        for ((a, b), (c, d)) in joined {
+            graph.insert_edge((a.clone(), b.clone()), (c.clone(), d.clone()));
+        }
+        graph
+    }
+
+    /// Returns `true` if the graphs have the same shape: some one to one mapping between their
+    /// keys turns the directed edges of one into the directed edges of the other.
+    /// Values and weights are ignored, and parallel edges count once.
+    ///
+    /// Searches the mappings by backtracking, only pairing nodes of equal in- and out-degree.
+    /// This takes exponential time in the worst case, so it is only meant for small graphs.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// let mut other = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
+    ///     other.insert(key * 10, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (2, 3), (3, 1)]);
+    /// other.extend_edges(vec![(30, 20), (20, 10), (10, 30)]);
+    /// assert!(graph.is_isomorphic_to(&other));
+    /// other.insert_edge(10, 20);
+    /// assert!(!graph.is_isomorphic_to(&other));
+    /// ```
+    pub fn is_isomorphic_to(&self, other: &IndexedGraph<K, V, E>) -> bool {
+        // the edges as a matrix over positions, with the (out, in) degree of every node
+        fn shape<K: Ord>(
+            adjacency: &BTreeMap<&K, BTreeSet<&K>>,
+        ) -> (Vec<Vec<bool>>, Vec<(usize, usize)>) {
+            let position: BTreeMap<&K, usize> = adjacency
+                .keys()
+                .enumerate()
+                .map(|(idx, key)| (*key, idx))
+                .collect();
+            let count = position.len();
+            let mut matrix = vec![vec![false; count]; count];
+            let mut degrees = vec![(0, 0); count];
+            for (from, targets) in adjacency {
+                for to in targets {
+                    matrix[position[from]][position[to]] = true;
+                    degrees[position[from]].0 += 1;
+                    degrees[position[to]].1 += 1;
+                }
+            }
+            (matrix, degrees)
+        }
+        fn extend(
+            ours: &(Vec<Vec<bool>>, Vec<(usize, usize)>),
+            theirs: &(Vec<Vec<bool>>, Vec<(usize, usize)>),
+            mapping: &mut Vec<usize>,
+            used: &mut [bool],
+        ) -> bool {
+            let node = mapping.len();
+            if node == used.len() {
+                return true;
+            }
+            for candidate in 0..used.len() {
+                if used[candidate] || ours.1[node] != theirs.1[candidate] {
+                    continue;
+                }
+                let consistent = ours.0[node][node] == theirs.0[candidate][candidate]
+                    && mapping.iter().enumerate().all(|(prev, mapped)| {
+                        ours.0[node][prev] == theirs.0[candidate][*mapped]
+                            && ours.0[prev][node] == theirs.0[*mapped][candidate]
+                    });
+                if consistent {
+                    used[candidate] = true;
+                    mapping.push(candidate);
+                    if extend(ours, theirs, mapping, used) {
+                        return true;
+                    }
+                    mapping.pop();
+                    used[candidate] = false;
+                }
+            }
+            false
+        }
+
+        let ours = shape(&self.directed_adjacency());
+        let theirs = shape(&other.directed_adjacency());
+        let mut our_degrees = ours.1.clone();
+        let mut their_degrees = theirs.1.clone();
+        our_degrees.sort_unstable();
+        their_degrees.sort_unstable();
+        if our_degrees != their_degrees {
+            return false;
+        }
+        let mut used = vec![false; theirs.1.len()];
+        extend(&ours, &theirs, &mut vec![], &mut used)
+    }
+
+    /// Sorts the keys topologically, so that every edge points from an earlier key to a later one.
+    /// Whenever several keys are ready at once, the smallest one according to `tie_break` comes
+    /// first, ties between those in key order.
+    ///
+    /// If the graph has a cycle, returns as `Err` the keys which couldn't be sorted, in key order:
+    /// the keys on a cycle and the ones reachable from a cycle.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 4), (2, 4), (3, 4)]);
+    /// assert_eq!(graph.topological_sort_by(|a, b| b.cmp(a)), Ok(vec![3, 2, 1, 4]));
+    /// assert_eq!(graph.topological_sort_by(|a, b| a.cmp(b)), Ok(vec![1, 2, 3, 4]));
+    ///
+    /// graph.insert_edge(4, 2);
+    /// assert_eq!(graph.topological_sort_by(|a, b| a.cmp(b)), Err(vec![2, 4]));
+    /// ```
+    pub fn topological_sort_by<F: FnMut(&K, &K) -> Ordering>(
+        &self,
+        mut tie_break: F,
+    ) -> Result<Vec<K>, Vec<K>> {
+        let adjacency = self.directed_adjacency();
+        let mut in_degrees: BTreeMap<&K, usize> = adjacency.keys().map(|key| (*key, 0)).collect();
+        for targets in adjacency.values() {
+            for to in targets {
+                *in_degrees.get_mut(to).unwrap() += 1;
+            }
+        }
+        let mut ready: Vec<&K> = in_degrees
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut sorted = vec![];
+        while !ready.is_empty() {
+            let mut first = 0;
+            for idx in 1..ready.len() {
+                if tie_break(ready[idx], ready[first]) == Ordering::Less {
+                    first = idx;
+                }
+            }
+            let node = ready.remove(first);
+            sorted.push(node.clone());
+            for next in &adjacency[node] {
+                let degree = in_degrees.get_mut(next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    let at = ready.binary_search(next).unwrap_or_else(|at| at);
+                    ready.insert(at, next);
+                }
+            }
+        }
+        if sorted.len() == adjacency.len() {
+            Ok(sorted)
+        } else {
+            Err(in_degrees
+                .into_iter()
+                .filter(|(_, degree)| *degree > 0)
+                .map(|(key, _)| key.clone())
+                .collect())
+        }
+    }
+
+    /// Calls `f` with every key and its values in topological order, as `topological_sort_by`
+    /// with keys ready at once visited in key order, but without collecting the order first.
+    ///
+    /// Stops at the first error returned by `f` and hands it back as `TopoError::Callback`.
+    /// If the graph has a cycle, the keys which can be sorted are visited before
+    /// `TopoError::Cycle` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::{IndexedGraph, TopoError};
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert("base", 1);
+    /// graph.insert("lib", 2);
+    /// graph.insert("app", 3);
+    /// graph.insert("app", 4);
+    /// graph.extend_edges(vec![("base", "lib"), ("lib", "app")]);
+    ///
+    /// let mut order = vec![];
+    /// let mut total = 0;
+    /// let done: Result<(), TopoError<()>> = graph.for_each_topological(|key, values| {
+    ///     order.push(*key);
+    ///     total += values.iter().copied().sum::<i32>();
+    ///     Ok(())
+    /// });
+    /// assert_eq!(done, Ok(()));
+    /// assert_eq!(order, ["base", "lib", "app"]);
+    /// assert_eq!(total, 10);
+    ///
+    /// let mut visited = 0;
+    /// let stopped = graph.for_each_topological(|key, _| {
+    ///     visited += 1;
+    ///     if *key == "lib" { Err("lib failed") } else { Ok(()) }
+    /// });
+    /// assert_eq!(stopped, Err(TopoError::Callback("lib failed")));
+    /// assert_eq!(visited, 2);
+    ///
+    /// graph.insert_edge("app", "lib");
+    /// assert_eq!(graph.for_each_topological(|_, _| Ok::<(), ()>(())), Err(TopoError::Cycle));
+    /// ```
+    pub fn for_each_topological<F, X>(&self, mut f: F) -> Result<(), TopoError<X>>
+    where
+        F: FnMut(&K, &[&V]) -> Result<(), X>,
+    {
+        let adjacency = self.directed_adjacency();
+        let mut in_degrees: BTreeMap<&K, usize> = adjacency.keys().map(|key| (*key, 0)).collect();
+        for targets in adjacency.values() {
+            for to in targets {
+                *in_degrees.get_mut(to).unwrap() += 1;
+            }
+        }
+        let mut ready: BTreeSet<&K> = in_degrees
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut visited = 0;
+        while let Some(node) = ready.pop_first() {
+            visited += 1;
+            let values: Vec<&V> = self.values_for(node).collect();
+            f(node, &values).map_err(TopoError::Callback)?;
+            for next in &adjacency[node] {
+                let degree = in_degrees.get_mut(next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.insert(next);
+                }
+            }
+        }
+        if visited == adjacency.len() {
+            Ok(())
+        } else {
+            Err(TopoError::Cycle)
+        }
+    }
+
+    /// Returns the rank of every key in a directed acyclic graph: the number of edges on the
+    /// longest path to it from a key without incoming edges. Keys of equal rank can be laid out
+    /// side by side, every edge points to a higher rank.
+    ///
+    /// If the graph has a cycle, returns the keys along one cycle as `Err`, in edge order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=4 {
+    ///     graph.insert(key, ());
+    /// }
+    /// graph.extend_edges(vec![(1, 2), (1, 3), (2, 4), (3, 4)]);
+    /// let ranks = graph.topological_ranks().unwrap();
+    /// assert_eq!(ranks[&1], 0);
+    /// assert_eq!(ranks[&2], 1);
+    /// assert_eq!(ranks[&4], 2);
+    ///
+    /// graph.insert_edge(4, 2);
+    /// assert_eq!(graph.topological_ranks(), Err(vec![4, 2]));
+    /// ```
+    pub fn topological_ranks(&self) -> Result<BTreeMap<K, usize>, Vec<K>> {
+        if let Some(depths) = self.topological_depths() {
+            return Ok(depths
+                .into_iter()
+                .map(|(key, rank)| (key.clone(), rank))
+                .collect());
+        }
+        // every key left over by the sort still has an incoming edge from another leftover key,
+        // so walking those edges backwards must run into a cycle
+        let stuck: BTreeSet<K> = self
+            .topological_sort_by(|a, b| a.cmp(b))
+            .err()
+            .into_iter()
+            .flatten()
+            .collect();
+        let reverse = self.reverse_adjacency();
+        let mut walk: Vec<&K> = vec![];
+        let mut seen: BTreeMap<&K, usize> = BTreeMap::new();
+        let mut node = stuck.iter().next().unwrap();
+        while !seen.contains_key(node) {
+            seen.insert(node, walk.len());
+            walk.push(node);
+            node = reverse[node]
+                .iter()
+                .copied()
+                .find(|from| stuck.contains(*from))
+                .unwrap();
+        }
+        Err(walk[seen[node]..]
+            .iter()
+            .rev()
+            .map(|key| (*key).clone())
+            .collect())
+    }
+
+    /// Collects the sources of the edges pointing to each key.
+    fn reverse_adjacency(&self) -> BTreeMap<&K, Vec<&K>> {
+        let mut reverse: BTreeMap<&K, Vec<&K>> = BTreeMap::new();
+        for (from, targets) in &self.edges {
+            for (to, _) in targets {
+                reverse.entry(to).or_default().push(from);
+            }
+        }
+        reverse
+    }
+
+    /// Depth-first search low-link pass over the undirected graph.
+    /// Finds the articulation points and the bridges together.
+    fn low_link(&self) -> (BTreeSet<K>, BTreeSet<(K, K)>) {
+        let adjacency = self.undirected_adjacency();
+        let mut order: BTreeMap<&K, usize> = BTreeMap::new();
+        let mut low: BTreeMap<&K, usize> = BTreeMap::new();
+        let mut points = BTreeSet::new();
+        let mut bridges = BTreeSet::new();
+        for root in adjacency.keys() {
+            if order.contains_key(root) {
+                continue;
+            }
+            order.insert(root, order.len());
+            low.insert(root, order[root]);
+            let mut root_children = 0;
+            let neighbors: Vec<&K> = adjacency[root].iter().copied().collect();
+            let mut calls = vec![(*root, None, neighbors, 0)];
+            while let Some((node, parent, neighbors, pos)) = calls.last_mut() {
+                let node = *node;
+                let parent = *parent;
+                let next = neighbors.get(*pos).copied();
+                *pos += 1;
+                if let Some(next) = next {
+                    if Some(next) == parent {
+                        continue;
+                    }
+                    if let Some(seen) = order.get(next) {
+                        let lowest = low[node].min(*seen);
+                        low.insert(node, lowest);
+                    } else {
+                        order.insert(next, order.len());
+                        low.insert(next, order[next]);
+                        let neighbors = adjacency[next].iter().copied().collect();
+                        calls.push((next, Some(node), neighbors, 0));
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(parent) = parent {
+                    let lowest = low[parent].min(low[node]);
+                    low.insert(parent, lowest);
+                    if parent == *root {
+                        root_children += 1;
+                    } else if low[node] >= order[parent] {
+                        points.insert(parent.clone());
+                    }
+                    if low[node] > order[parent] {
+                        let bridge = if parent < node {
+                            (parent.clone(), node.clone())
+                        } else {
+                            (node.clone(), parent.clone())
+                        };
+                        bridges.insert(bridge);
+                    }
+                }
+            }
+            if root_children > 1 {
+                points.insert((*root).clone());
+            }
+        }
+        (points, bridges)
+    }
+
+    /// Breadth-first search returning the number of edges from `start` to every node it reaches.
+    fn distances<'a>(
+        adjacency: &BTreeMap<&'a K, BTreeSet<&'a K>>,
+        start: &'a K,
+    ) -> BTreeMap<&'a K, usize> {
+        let mut distances = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(start, 0);
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node] + 1;
+            for next in adjacency.get(node).into_iter().flatten() {
+                if !distances.contains_key(next) {
+                    distances.insert(*next, distance);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        distances
+    }
+
+    /// Collects the targets of the edges of every node.
+    /// Only keys present in the graph take part.
+    fn directed_adjacency(&self) -> BTreeMap<&K, BTreeSet<&K>> {
+        let mut adjacency: BTreeMap<&K, BTreeSet<&K>> =
+            self.i.keys().map(|key| (key, BTreeSet::new())).collect();
+        for (from, targets) in &self.edges {
+            if let Some(neighbors) = adjacency.get_mut(from) {
+                neighbors.extend(
+                    targets
+                        .iter()
+                        .map(|(to, _)| to)
+                        .filter(|to| self.i.contains_key(to)),
+                );
+            }
+        }
+        adjacency
+    }
+
+    /// Iterates every edge with its weight, sorted by source and then in insertion order.
+    fn edge_triples(&self) -> impl Iterator<Item = (&K, &K, &E)> + '_ {
+        self.edges
+            .iter()
+            .flat_map(|(from, targets)| targets.iter().map(move |(to, weight)| (from, to, weight)))
+    }
+
+    /// Returns the length of the longest path from a key without incoming edges to every key,
+    /// found by Kahn's topological sort. Returns `None` if the graph has a cycle.
+    fn topological_depths(&self) -> Option<BTreeMap<&K, usize>> {
+        let adjacency = self.directed_adjacency();
+        let mut in_degrees: BTreeMap<&K, usize> = adjacency.keys().map(|key| (*key, 0)).collect();
+        for targets in adjacency.values() {
+            for to in targets {
+                *in_degrees.get_mut(to).unwrap() += 1;
+            }
+        }
+        let mut ready: VecDeque<&K> = in_degrees
+            .iter()
+            .filter(|(_, degree)| **degree == 0)
+            .map(|(key, _)| *key)
+            .collect();
+        let mut depths: BTreeMap<&K, usize> = ready.iter().map(|key| (*key, 0)).collect();
+        let mut visited = 0;
+        while let Some(node) = ready.pop_front() {
+            visited += 1;
+            let depth = depths[node] + 1;
+            for next in &adjacency[node] {
+                let known = depths.entry(next).or_insert(depth);
+                *known = (*known).max(depth);
+                let degree = in_degrees.get_mut(next).unwrap();
+                *degree -= 1;
+                if *degree == 0 {
+                    ready.push_back(next);
+                }
+            }
+        }
+        if visited == adjacency.len() {
+            Some(depths)
+        } else {
+            None
+        }
+    }
+
+    /// Removes the entries for which `remove` returns `true`, given their position and key.
+    /// Returns the removed entries in insertion order and rebuilds the index once.
+    fn remove_entries<F: FnMut(usize, &K) -> bool>(&mut self, mut remove: F) -> Vec<(K, V)> {
+        let keys = std::mem::take(&mut self.keys);
+        let values = std::mem::take(&mut self.values);
+        let mut removed = vec![];
+        for (pos, (key, value)) in keys.into_iter().zip(values).enumerate() {
+            if remove(pos, &key) {
+                removed.push((key, value));
+            } else {
+                self.keys.push(key);
+                self.values.push(value);
+            }
+        }
+        self.rebuild_index();
+        removed
+    }
+
+    /// Recomputes the positional index from `keys`.
+    /// Needed after any change which moves entries around in the backing vectors.
+    fn rebuild_index(&mut self) {
+        self.i.clear();
+        for (idx, key) in self.keys.iter().enumerate() {
+            if let Some(indexes) = self.i.get_mut(key) {
+                indexes.push(idx);
+            } else {
+                self.i.insert(key.clone(), vec![idx]);
+            }
+        }
+    }
+}
+
+/// An empty graph, the same as `IndexedGraph::new_weighted`.
+///
+/// # Examples
+///
+/// ```
+/// use igraph::IndexedGraph;
+///
+/// let graph: IndexedGraph<u8, &str> = Default::default();
+/// assert!(graph.is_empty());
+/// assert_eq!(graph.edge_count(), 0);
+/// ```
+impl<K: Ord + Clone, V, E> Default for IndexedGraph<K, V, E> {
+    fn default() -> Self {
+        IndexedGraph::new_weighted()
+    }
+}
+
+/// An immutable graph, made by `IndexedGraph::freeze`.
+/// The storage is kept in boxed slices without spare capacity.
+/// It is `Sync` whenever its keys, values and weights are, so it can be shared between threads.
+#[derive(Debug, Clone)]
+pub struct FrozenGraph<K, V, E = ()> {
+    keys: Box<[K]>,
+    values: Box<[V]>,
+    edges: BTreeMap<K, Box<[(K, E)]>>,
+    i: BTreeMap<K, Box<[usize]>>,
+    max_nodes: Option<usize>,
+}
+
+impl<K: Ord + Clone, V, E> FrozenGraph<K, V, E> {
+    /// Returns a reference to the values corresponding to the key.
+    ///
+    /// # Examples
     ///
     /// ```
     /// use igraph::IndexedGraph;
     ///
-    /// let mut a = IndexedGraph::new();
-    /// assert!(a.is_empty());
-    /// a.insert(1, "a");
-    /// assert!(!a.is_empty());
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// let frozen = graph.freeze();
+    /// assert_eq!(frozen.get(&1), vec![&"a"]);
+    /// assert_eq!(frozen.get(&2), Vec::<&&str>::new());
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.i.len() == 0
+    pub fn get(&self, key: &K) -> Vec<&V> {
+        let mut res = vec![];
+        if let Some(indexes) = self.i.get(key) {
+            for idx in indexes.iter() {
+                res.push(&self.values[*idx]);
+            }
+        }
+        res
     }
 
-    pub fn index_copy(&self) -> BTreeMap<K, Vec<usize>> {
-        self.i.clone().into()
+    /// Returns the key-value pairs corresponding to the supplied key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// let frozen = graph.freeze();
+    /// assert_eq!(frozen.get_key_values(&1), vec![(&1, &"a")]);
+    /// ```
+    pub fn get_key_values(&self, key: &K) -> Vec<(&K, &V)> {
+        let mut res = vec![];
+        if let Some((key, indexes)) = self.i.get_key_value(key) {
+            for idx in indexes.iter() {
+                res.push((key, &self.values[*idx]));
+            }
+        }
+        res
     }
 
-    /// Returns a Vec of references to the values corresponding to the supplied key.
+    /// Returns `true` if the graph contains a value for the specified key.
     ///
     /// # Examples
     ///
     /// ```
     /// use igraph::IndexedGraph;
     ///
-    /// let mut a = IndexedGraph::new();
-    /// a.insert(1, "a");
-    /// assert_eq!(*a.index(1), [&"a"]);
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// let frozen = graph.freeze();
+    /// assert!(frozen.contains_key(&1));
+    /// assert!(!frozen.contains_key(&2));
     /// ```
-    #[inline]
-    pub fn index(&self, key: K) -> Vec<&V> {
-        self.get(&key)
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.i.contains_key(key)
     }
 
-    /// Gets an iterator over the entries of the graph, sorted by key.
-    /// `IndexedGraph` preserves the order of insertion for `iter()`.
+    /// Returns the number of distinct keys in the graph.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(1, "b");
+    /// assert_eq!(graph.freeze().len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.i.len()
+    }
+
+    /// Returns `true` if the graph contains no elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let graph: IndexedGraph<u8, u8> = IndexedGraph::new();
+    /// assert!(graph.freeze().is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.i.is_empty()
+    }
+
+    /// Returns the number of edges in the graph, parallel edges are counted one by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert_edge(1, 1);
+    /// assert_eq!(graph.freeze().edge_count(), 1);
+    /// ```
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(|targets| targets.len()).sum()
+    }
+
+    /// Gets an iterator over the entries of the graph, in insertion order.
     ///
     /// # Examples
     ///
@@ -343,41 +4912,244 @@ impl<K: Ord + Clone, V> IndexedGraph<K, V> {
     /// use igraph::IndexedGraph;
     ///
     /// let mut graph = IndexedGraph::new();
-    /// graph.insert(3, "c");
     /// graph.insert(2, "b");
     /// graph.insert(1, "a");
+    /// let frozen = graph.freeze();
+    /// assert_eq!(frozen.iter().collect::<Vec<_>>(), [(&2, &"b"), (&1, &"a")]);
+    /// ```
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&K, &V)> + ExactSizeIterator + '_ {
+        self.keys.iter().zip(self.values.iter())
+    }
+
+    /// Returns the keys reachable from `start` in breadth-first order, beginning with `start`.
     ///
-    /// for (key, value) in graph.iter() {
-    ///     println!("{}: {}", key, value);
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// for key in 1..=3 {
+    ///     graph.insert(key, ());
     /// }
+    /// graph.insert_edge(1, 2);
+    /// graph.insert_edge(2, 3);
+    /// let frozen = graph.freeze();
     ///
-    /// let (first_key, first_value) = graph.iter().next().unwrap();
-    /// assert_eq!((*first_key, *first_value), (3, "c"));
+    /// // the frozen graph can be read from many threads at once
+    /// std::thread::scope(|scope| {
+    ///     scope.spawn(|| assert_eq!(frozen.bfs(&1), [&1, &2, &3]));
+    ///     scope.spawn(|| assert_eq!(frozen.bfs(&2), [&2, &3]));
+    /// });
     /// ```
-    pub fn iter(&self) -> Iter<'_, K, V> {
-        Iter {
-            graph: &self,
-            length: self.len(),
+    pub fn bfs<'a>(&'a self, start: &'a K) -> Vec<&'a K> {
+        let mut order = vec![start];
+        let mut seen = BTreeSet::new();
+        seen.insert(start);
+        let mut next = 0;
+        while next < order.len() {
+            for (to, _) in self
+                .edges
+                .get(order[next])
+                .into_iter()
+                .flat_map(|t| t.iter())
+            {
+                if seen.insert(to) {
+                    order.push(to);
+                }
+            }
+            next += 1;
+        }
+        order
+    }
+
+    /// Turns the frozen graph back into an `IndexedGraph`, which can be changed again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// let mut graph = graph.freeze().thaw();
+    /// graph.insert(2, "b");
+    /// assert_eq!(graph.len(), 2);
+    /// ```
+    pub fn thaw(self) -> IndexedGraph<K, V, E> {
+        IndexedGraph {
+            keys: self.keys.into_vec(),
+            values: self.values.into_vec(),
+            edges: self
+                .edges
+                .into_iter()
+                .map(|(from, targets)| (from, targets.into_vec()))
+                .collect(),
+            i: self
+                .i
+                .into_iter()
+                .map(|(key, indexes)| (key, indexes.into_vec()))
+                .collect(),
+            max_nodes: self.max_nodes,
+        }
+    }
+}
+
+/// What `IndexedGraph::insert_with_policy` does when the key is already in the graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicatePolicy {
+    /// Insert the value after the existing ones, like `insert`.
+    Append,
+    /// Overwrite the latest value of the key in place.
+    Replace,
+    /// Leave the graph unchanged and hand the value back.
+    Reject,
+}
+
+/// The error returned by `IndexedGraph::try_insert_edge` when an end of the edge is not in the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeError<K> {
+    /// The source key of the edge is missing.
+    MissingFrom(K),
+    /// The target key of the edge is missing.
+    MissingTo(K),
+}
+
+impl<K: fmt::Debug> fmt::Display for EdgeError<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EdgeError::MissingFrom(key) => write!(f, "edge source {:?} is not in the graph", key),
+            EdgeError::MissingTo(key) => write!(f, "edge target {:?} is not in the graph", key),
+        }
+    }
+}
+
+impl<K: fmt::Debug> std::error::Error for EdgeError<K> {}
+
+/// The error returned by `IndexedGraph::insert_edge_acyclic` when the edge would close a cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleError;
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "edge would create a cycle")
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+/// The error returned by `IndexedGraph::for_each_topological`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TopoError<E> {
+    /// The graph has a cycle, so not every key could be visited.
+    Cycle,
+    /// The callback failed with this error.
+    Callback(E),
+}
+
+impl<E: fmt::Display> fmt::Display for TopoError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopoError::Cycle => write!(f, "graph has a cycle"),
+            TopoError::Callback(error) => write!(f, "callback failed: {}", error),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for TopoError<E> {}
+
+/// The state recorded by `IndexedGraph::checkpoint`, to go back to with `IndexedGraph::rollback_to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Checkpoint<K> {
+    entries: usize,
+    edges: BTreeMap<K, usize>,
+}
+
+/// The error returned by `IndexedGraph::rollback_to` when entries or edges were removed
+/// since the checkpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RollbackError;
+
+impl fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph shrunk since the checkpoint")
+    }
+}
+
+impl std::error::Error for RollbackError {}
+
+/// How many entries and edges `Display` shows before cutting the list short.
+const DISPLAY_LIMIT: usize = 5;
+
+/// Formats a one line summary of the graph for logs: its node and edge counts,
+/// then the first few entries in insertion order and the first few edges sorted by source.
+/// Longer lists end with an ellipsis.
+///
+/// # Examples
+///
+/// ```
+/// use igraph::IndexedGraph;
+///
+/// let mut graph = IndexedGraph::new();
+/// graph.insert(1, "a");
+/// graph.insert(2, "b");
+/// graph.insert_edge(1, 2);
+/// assert_eq!(graph.to_string(), "2 nodes, 1 edges: {1: a, 2: b} [1 -> 2]");
+///
+/// for key in 3..10 {
+///     graph.insert(key, "c");
+/// }
+/// assert!(graph.to_string().starts_with("9 nodes, 1 edges: {1: a, 2: b, 3: c, 4: c, 5: c, ...}"));
+/// ```
+impl<K: Ord + Clone + fmt::Display, V: fmt::Display, E> fmt::Display for IndexedGraph<K, V, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} nodes, {} edges: {{",
+            self.node_count(),
+            self.edge_count()
+        )?;
+        for (idx, (key, value)) in self.iter().enumerate() {
+            if idx == DISPLAY_LIMIT {
+                write!(f, ", ...")?;
+                break;
+            }
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", key, value)?;
+        }
+        write!(f, "}} [")?;
+        for (idx, (from, to, _)) in self.edge_triples().enumerate() {
+            if idx == DISPLAY_LIMIT {
+                write!(f, ", ...")?;
+                break;
+            }
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{} -> {}", from, to)?;
         }
+        write!(f, "]")
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct Iter<'a, K: 'a, V: 'a> {
-    graph: &'a IndexedGraph<K, V>,
+pub struct Iter<'a, K: 'a, V: 'a, E: 'a = ()> {
+    graph: &'a IndexedGraph<K, V, E>,
     length: usize,
 }
 
-impl<'a, K: Ord + Clone, V> IntoIterator for &'a IndexedGraph<K, V> {
+impl<'a, K: Ord + Clone, V, E> IntoIterator for &'a IndexedGraph<K, V, E> {
     type Item = (&'a K, &'a V);
-    type IntoIter = Iter<'a, K, V>;
+    type IntoIter = Iter<'a, K, V, E>;
 
-    fn into_iter(self) -> Iter<'a, K, V> {
+    fn into_iter(self) -> Iter<'a, K, V, E> {
         self.iter()
     }
 }
 
-impl<'a, K: 'a + Ord + Clone, V: 'a> Iterator for Iter<'a, K, V> {
+impl<'a, K: 'a + Ord + Clone, V: 'a, E: 'a> Iterator for Iter<'a, K, V, E> {
     type Item = (&'a K, &'a V);
 
     fn next(&mut self) -> Option<(&'a K, &'a V)> {
@@ -385,7 +5157,7 @@ impl<'a, K: 'a + Ord + Clone, V: 'a> Iterator for Iter<'a, K, V> {
             None
         } else {
             self.length -= 1;
-            let idx = &self.graph.len() - 1 - self.length;
+            let idx = self.graph.keys.len() - 1 - self.length;
             Some((&self.graph.keys[idx], &self.graph.values[idx]))
         }
     }
@@ -407,9 +5179,9 @@ impl<'a, K: 'a + Ord + Clone, V: 'a> Iterator for Iter<'a, K, V> {
     }
 }
 
-impl<K: Ord + Clone, V> FusedIterator for Iter<'_, K, V> {}
+impl<K: Ord + Clone, V, E> FusedIterator for Iter<'_, K, V, E> {}
 
-impl<'a, K: 'a + Ord + Clone, V: 'a> DoubleEndedIterator for Iter<'a, K, V> {
+impl<'a, K: 'a + Ord + Clone, V: 'a, E: 'a> DoubleEndedIterator for Iter<'a, K, V, E> {
     fn next_back(&mut self) -> Option<(&'a K, &'a V)> {
         if self.length == 0 {
             None
@@ -421,7 +5193,7 @@ impl<'a, K: 'a + Ord + Clone, V: 'a> DoubleEndedIterator for Iter<'a, K, V> {
     }
 }
 
-impl<K: Ord + Clone, V> ExactSizeIterator for Iter<'_, K, V> {
+impl<K: Ord + Clone, V, E> ExactSizeIterator for Iter<'_, K, V, E> {
     fn len(&self) -> usize {
         self.length
     }
@@ -435,3 +5207,193 @@ impl<K: Ord + Clone, V> ExactSizeIterator for Iter<'_, K, V> {
 //         self.get(&index).as_slice()
 //     }
 // }
+
+#[cfg(feature = "serde")]
+impl<K: Ord + Clone + serde::Serialize, V: serde::Serialize, E: serde::Serialize>
+    IndexedGraph<K, V, E>
+{
+    /// Writes the graph as JSON lines, one object per line and nothing held in memory.
+    /// First a `{"key":...,"value":...}` line for every entry in insertion order,
+    /// then a `{"from":...,"to":...,"weight":...}` line for every edge, sorted by source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert(1, "a");
+    /// graph.insert(2, "b");
+    /// graph.insert_edge(1, 2);
+    ///
+    /// let mut buffer = Vec::new();
+    /// graph.write_jsonl(&mut buffer).unwrap();
+    /// let text = String::from_utf8(buffer).unwrap();
+    /// assert_eq!(text.lines().count(), 3);
+    /// assert_eq!(text.lines().next(), Some(r#"{"key":1,"value":"a"}"#));
+    /// assert_eq!(text.lines().last(), Some(r#"{"from":1,"to":2,"weight":null}"#));
+    /// ```
+    pub fn write_jsonl<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        #[derive(serde::Serialize)]
+        struct Entry<'a, K, V> {
+            key: &'a K,
+            value: &'a V,
+        }
+        #[derive(serde::Serialize)]
+        struct Edge<'a, K, E> {
+            from: &'a K,
+            to: &'a K,
+            weight: &'a E,
+        }
+
+        for (key, value) in self.iter() {
+            serde_json::to_writer(&mut writer, &Entry { key, value })?;
+            writer.write_all(b"\n")?;
+        }
+        for (from, targets) in &self.edges {
+            for (to, weight) in targets {
+                serde_json::to_writer(&mut writer, &Edge { from, to, weight })?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<K: Ord + Clone + fmt::Display, V, E> IndexedGraph<K, V, E> {
+    /// Writes the edges as CSV, with a `source,target` header and one row per edge sorted by
+    /// source. Keys containing a comma, a quote or a line break are quoted, with quotes doubled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new();
+    /// graph.insert("a".to_string(), ());
+    /// graph.insert("b,c".to_string(), ());
+    /// graph.insert_edge("a".to_string(), "b,c".to_string());
+    /// graph.insert_edge("b,c".to_string(), "a".to_string());
+    ///
+    /// let mut buffer = Vec::new();
+    /// graph.write_csv(&mut buffer).unwrap();
+    /// let text = String::from_utf8(buffer).unwrap();
+    /// assert_eq!(text, "source,target\na,\"b,c\"\n\"b,c\",a\n");
+    /// ```
+    pub fn write_csv<W: io::Write>(&self, mut writer: W) -> io::Result<()> {
+        fn field(key: &impl fmt::Display) -> String {
+            let text = key.to_string();
+            if text.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            } else {
+                text
+            }
+        }
+
+        writer.write_all(b"source,target\n")?;
+        for (from, to, _) in self.edge_triples() {
+            writeln!(writer, "{},{}", field(from), field(to))?;
+        }
+        Ok(())
+    }
+
+    /// Formats every node on its own line, sorted by key, followed by its out-neighbors
+    /// with the edge weights in parentheses, like `a -> b (3), c (5)`.
+    /// A node without outgoing edges is listed as `a ->`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use igraph::IndexedGraph;
+    ///
+    /// let mut graph = IndexedGraph::new_weighted();
+    /// graph.insert("a", ());
+    /// graph.insert("b", ());
+    /// graph.insert("c", ());
+    /// graph.insert_weighted_edge("a", "b", 3);
+    /// graph.insert_weighted_edge("a", "c", 5);
+    /// graph.insert_weighted_edge("b", "c", 1);
+    /// assert_eq!(graph.to_adjacency_string(), "a -> b (3), c (5)\nb -> c (1)\nc ->\n");
+    /// ```
+    pub fn to_adjacency_string(&self) -> String
+    where
+        E: fmt::Display,
+    {
+        let mut text = String::new();
+        for key in self.i.keys() {
+            let targets: Vec<String> = self
+                .edges
+                .get(key)
+                .into_iter()
+                .flatten()
+                .map(|(to, weight)| format!("{} ({})", to, weight))
+                .collect();
+            if targets.is_empty() {
+                text.push_str(&format!("{} ->\n", key));
+            } else {
+                text.push_str(&format!("{} -> {}\n", key, targets.join(", ")));
+            }
+        }
+        text
+    }
+}
+
+/// Graphs are equal when they hold the same entries and the same edges.
+/// Entries are compared in key order, so the insertion order of different keys doesn't matter,
+/// but the values of a duplicate key are compared in their insertion order.
+/// Edges are compared by source key, then in insertion order.
+impl<K: Ord + Clone, V: PartialEq, E: PartialEq> PartialEq for IndexedGraph<K, V, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.range(..).eq(other.range(..)) && self.edge_triples().eq(other.edge_triples())
+    }
+}
+
+impl<K: Ord + Clone, V: Eq, E: Eq> Eq for IndexedGraph<K, V, E> {}
+
+/// Orders graphs lexicographically, first by their sorted lists of keys,
+/// then by their values in the same order, then by their edges as `(from, to, weight)`
+/// sorted by source key. This is consistent with `PartialEq`, but it is not a structural order.
+///
+/// # Examples
+///
+/// ```
+/// use igraph::IndexedGraph;
+///
+/// let mut small = IndexedGraph::new();
+/// small.insert(1, "b");
+/// let mut large = IndexedGraph::new();
+/// large.insert(2, "a");
+/// assert!(small < large);
+///
+/// let mut linked = small.clone();
+/// linked.insert_edge(1, 1);
+/// assert!(small < linked);
+///
+/// let mut graphs = vec![linked.clone(), large.clone(), small.clone()];
+/// graphs.sort();
+/// assert_eq!(graphs, [small, linked, large]);
+/// ```
+impl<K: Ord + Clone, V: PartialOrd, E: PartialOrd> PartialOrd for IndexedGraph<K, V, E> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let keys = self.range(..).map(|(key, _)| key);
+        match keys.cmp(other.range(..).map(|(key, _)| key)) {
+            Ordering::Equal => {}
+            ordering => return Some(ordering),
+        }
+        let values = self.range(..).map(|(_, value)| value);
+        match values.partial_cmp(other.range(..).map(|(_, value)| value)) {
+            Some(Ordering::Equal) => self.edge_triples().partial_cmp(other.edge_triples()),
+            ordering => ordering,
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Ord, E: Ord> Ord for IndexedGraph<K, V, E> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let keys = self.range(..).map(|(key, _)| key);
+        let values = self.range(..).map(|(_, value)| value);
+        keys.cmp(other.range(..).map(|(key, _)| key))
+            .then_with(|| values.cmp(other.range(..).map(|(_, value)| value)))
+            .then_with(|| self.edge_triples().cmp(other.edge_triples()))
+    }
+}